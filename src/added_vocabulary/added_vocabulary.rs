@@ -0,0 +1,202 @@
+use serde::{Deserialize, Serialize};
+
+use crate::word_tokenize::tone_normalize;
+
+/// A user-registered token (special marker, brand name, product code, ...)
+/// that must survive tokenization as a single atomic span.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AddedToken {
+    pub content: String,
+    /// Only match when the surrounding characters are not word characters
+    /// (i.e. require a token boundary, not a match mid-syllable).
+    pub single_word: bool,
+    /// Consume adjacent whitespace to the left of the match.
+    pub lstrip: bool,
+    /// Consume adjacent whitespace to the right of the match.
+    pub rstrip: bool,
+    /// Match against the canonicalized form of the input (lookalike codepoints
+    /// folded, tones repositioned to new style) instead of the raw text, so
+    /// e.g. `"hoà"` and `"hòa"` both match a token registered as `"hòa"`.
+    pub normalized: bool,
+}
+
+impl AddedToken {
+    pub fn new(content: impl Into<String>) -> Self {
+        Self {
+            content: content.into(),
+            single_word: false,
+            lstrip: false,
+            rstrip: false,
+            normalized: false,
+        }
+    }
+
+    pub fn with_normalized(mut self, normalized: bool) -> Self {
+        self.normalized = normalized;
+        self
+    }
+}
+
+/// Alias for `AddedToken` under the name used by `VietnameseTokenizer`'s
+/// fixed-word vocabulary API (`with_fixed_tokens` and the tokenizer's
+/// `fixed_words`): a fixed word is just an `AddedToken` that is passed to the
+/// tokenizer's constructor rather than added at runtime, so both share this
+/// one struct instead of two near-identical ones.
+pub type FixedWord = AddedToken;
+
+/// Holds the set of `AddedToken`s that are matched against raw input *before*
+/// the regular tokenizer patterns run, so they come out as single atomic
+/// tokens and are never touched by normalization.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AddedVocabulary {
+    tokens: Vec<AddedToken>,
+}
+
+impl AddedVocabulary {
+    pub fn new() -> Self {
+        Self { tokens: Vec::new() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    pub fn add_tokens(&mut self, tokens: impl IntoIterator<Item = AddedToken>) {
+        for token in tokens {
+            if let Some(existing) = self.tokens.iter_mut().find(|t| t.content == token.content) {
+                *existing = token;
+            } else {
+                self.tokens.push(token);
+            }
+        }
+    }
+
+    /// Repoint an already-registered token's surface content to `new` without
+    /// changing its position in the list (i.e. without changing its id slot).
+    pub fn assign_token(&mut self, old: &str, new: &str) -> bool {
+        match self.tokens.iter_mut().find(|t| t.content == old) {
+            Some(token) => {
+                token.content = new.to_string();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Split `text` into segments, each tagged with whether it is a literal
+    /// added-token match (`true`) or ordinary text to hand to the regular
+    /// tokenizer (`false`). Leftmost match wins when entries overlap; longest
+    /// content breaks ties among candidates that start at the same position
+    /// (so a later, longer token can never preempt an earlier, shorter one).
+    pub fn split(&self, text: &str) -> Vec<(bool, String)> {
+        if self.tokens.is_empty() || text.is_empty() {
+            return vec![(false, text.to_string())];
+        }
+
+        let tokens: Vec<&AddedToken> = self.tokens.iter().collect();
+
+        let mut segments = Vec::new();
+        let mut rest = text;
+
+        while !rest.is_empty() {
+            let mut best: Option<(usize, usize, &AddedToken)> = None;
+
+            for token in &tokens {
+                if token.content.is_empty() {
+                    continue;
+                }
+                if let Some((byte_pos, byte_len)) = find_token_match(rest, token) {
+                    if token.single_word && !matches_at_word_boundary(rest, byte_pos, byte_len) {
+                        continue;
+                    }
+                    let better = match best {
+                        None => true,
+                        Some((best_pos, best_len, _)) => {
+                            byte_pos < best_pos || (byte_pos == best_pos && byte_len > best_len)
+                        }
+                    };
+                    if better {
+                        best = Some((byte_pos, byte_len, token));
+                    }
+                }
+            }
+
+            let Some((byte_pos, byte_len, token)) = best else {
+                // No added token matched anywhere in the remainder.
+                segments.push((false, rest.to_string()));
+                break;
+            };
+
+            let raw_match = rest[byte_pos..byte_pos + byte_len].to_string();
+            let mut match_start = byte_pos;
+            let mut match_end = byte_pos + byte_len;
+
+            if token.lstrip {
+                while match_start > 0
+                    && rest[..match_start].chars().next_back().map_or(false, |c| c.is_whitespace())
+                {
+                    match_start -= rest[..match_start].chars().next_back().unwrap().len_utf8();
+                }
+            }
+            if token.rstrip {
+                while match_end < rest.len()
+                    && rest[match_end..].chars().next().map_or(false, |c| c.is_whitespace())
+                {
+                    match_end += rest[match_end..].chars().next().unwrap().len_utf8();
+                }
+            }
+
+            if match_start > 0 {
+                segments.push((false, rest[..match_start].to_string()));
+            }
+            segments.push((true, raw_match));
+            rest = &rest[match_end..];
+        }
+
+        segments
+    }
+}
+
+/// Find the first occurrence of `token.content` in `rest`, returning its
+/// `(byte_pos, byte_len)` span in `rest`. For a `normalized` token, both sides
+/// are compared in canonical form (see `tone_normalize::canonicalize_for_matching`)
+/// and the canonical char offset is mapped back to a raw byte span, since
+/// canonicalization never changes a string's char count.
+fn find_token_match(rest: &str, token: &AddedToken) -> Option<(usize, usize)> {
+    if !token.normalized {
+        return rest.find(token.content.as_str()).map(|pos| (pos, token.content.len()));
+    }
+
+    let canonical_rest = tone_normalize::canonicalize_for_matching(rest);
+    let canonical_content = tone_normalize::canonicalize_for_matching(&token.content);
+    let canonical_byte_pos = canonical_rest.find(&canonical_content)?;
+
+    let char_start = canonical_rest[..canonical_byte_pos].chars().count();
+    let char_len = canonical_content.chars().count();
+
+    let mut chars = rest.char_indices();
+    let byte_start = chars.nth(char_start)?.0;
+    let byte_end = chars
+        .nth(char_len.saturating_sub(1))
+        .map(|(pos, _)| pos)
+        .unwrap_or(rest.len());
+
+    Some((byte_start, byte_end - byte_start))
+}
+
+fn matches_at_word_boundary(haystack: &str, start: usize, len: usize) -> bool {
+    let before_ok = haystack[..start]
+        .chars()
+        .next_back()
+        .map_or(true, |c| !is_word_char(c));
+    let end = start + len;
+    let after_ok = haystack[end..]
+        .chars()
+        .next()
+        .map_or(true, |c| !is_word_char(c));
+    before_ok && after_ok
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
@@ -0,0 +1,3 @@
+pub mod added_vocabulary;
+
+pub use added_vocabulary::{AddedToken, AddedVocabulary, FixedWord};
@@ -1,7 +1,11 @@
 use pyo3::{
+    exceptions::PyValueError,
     prelude::*,
+    types::PyBytes,
 };
+use rayon::prelude::*;
 
+use crate::subword::SubwordEncoder;
 use crate::word_tokenize::{VietnameseWordSegmenter, VietnameseTokenizer};
 
 
@@ -49,6 +53,62 @@ impl VietnameseWordSegmenterPy {
             &fixed_words,
         )
     }
+
+    /// Return the top `n_best` segmentations from the CRF model's beam
+    /// search, each paired with a normalized probability.
+    fn word_tokenize_nbest(
+        &self,
+        sentence: &str,
+        beam_width: usize,
+        n_best: usize,
+    ) -> Vec<(Vec<String>, f64)> {
+        self.segmenter.word_tokenize_nbest(sentence, beam_width, n_best)
+    }
+
+    /// Register special tokens (`<s>`, `</s>`, `<mask>`, ...) that must never
+    /// be split during tokenization and are never touched by normalization.
+    fn add_special_tokens(&mut self, tokens: Vec<String>) {
+        self.segmenter.add_special_tokens(&tokens);
+    }
+
+    /// Repoint an already-registered added token to a new surface string
+    /// without changing its id slot. Returns `False` if `old` was never
+    /// registered.
+    fn assign_token(&mut self, old: &str, new: &str) -> bool {
+        self.segmenter.assign_token(old, new)
+    }
+
+    /// Word-tokenize a whole batch in parallel across cores, releasing the
+    /// GIL for the duration of the Rust work.
+    fn word_tokenize_batch(&self, py: Python, sentences: Vec<String>) -> Vec<Vec<String>> {
+        py.allow_threads(|| {
+            sentences
+                .par_iter()
+                .map(|sentence| self.segmenter.word_tokenize(sentence))
+                .collect()
+        })
+    }
+
+    fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let bytes = bincode::serialize(&self.segmenter)
+            .map_err(|e| PyValueError::new_err(format!("failed to pickle segmenter: {}", e)))?;
+        Ok(PyBytes::new_bound(py, &bytes))
+    }
+
+    fn __setstate__(&mut self, state: &Bound<PyBytes>) -> PyResult<()> {
+        self.segmenter = bincode::deserialize(state.as_bytes())
+            .map_err(|e| PyValueError::new_err(format!("failed to unpickle segmenter: {}", e)))?;
+        Ok(())
+    }
+
+    /// Rebuild via the class's own `__new__` (no-arg constructor), then restore
+    /// state through `__setstate__`.
+    fn __reduce__(slf: &Bound<Self>) -> PyResult<(PyObject, (), PyObject)> {
+        let py = slf.py();
+        let cls = slf.get_type().unbind();
+        let state = slf.borrow().__getstate__(py)?.unbind().into();
+        Ok((cls, (), state))
+    }
 }
 
 #[pyclass]
@@ -77,22 +137,103 @@ impl VietnameseTokenizerPy {
         self.tokenizer.tokenize(text)
     }
 
-    fn tokenize_with_tags(&self, text: &str, include_tags: bool) -> Vec<(String, String)> {
+    fn tokenize_with_tags(
+        &self,
+        text: &str,
+        include_tags: bool,
+    ) -> Vec<(String, String, usize, usize)> {
         let tokens = self.tokenizer.tokenize_with_tags(text, include_tags);
         tokens
             .into_iter()
-            .map(|token| (token.text, format!("{:?}", token.token_type)))
+            .map(|token| (token.text, format!("{:?}", token.token_type), token.start, token.end))
             .collect()
     }
 
     fn tokenize_as_text(&self, text: &str) -> String {
         self.tokenizer.tokenize_as_text(text)
     }
+
+    /// Register special tokens (`<s>`, `</s>`, `<mask>`, ...) that must never
+    /// be split by tokenization and are never touched by normalization.
+    fn add_special_tokens(&mut self, tokens: Vec<String>) {
+        self.tokenizer.add_special_tokens(&tokens);
+    }
+
+    /// Repoint an already-registered added token to a new surface string
+    /// without changing its id slot. Returns `False` if `old` was never
+    /// registered.
+    fn assign_token(&mut self, old: &str, new: &str) -> bool {
+        self.tokenizer.assign_token(old, new)
+    }
+
+    /// Tokenize a whole batch in parallel across cores, releasing the GIL for
+    /// the duration of the Rust work.
+    fn tokenize_batch(&self, py: Python, texts: Vec<String>) -> Vec<Vec<String>> {
+        py.allow_threads(|| {
+            texts
+                .par_iter()
+                .map(|text| self.tokenizer.tokenize(text))
+                .collect()
+        })
+    }
+
+    fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let bytes = bincode::serialize(&self.tokenizer)
+            .map_err(|e| PyValueError::new_err(format!("failed to pickle tokenizer: {}", e)))?;
+        Ok(PyBytes::new_bound(py, &bytes))
+    }
+
+    fn __setstate__(&mut self, state: &Bound<PyBytes>) -> PyResult<()> {
+        self.tokenizer = bincode::deserialize(state.as_bytes())
+            .map_err(|e| PyValueError::new_err(format!("failed to unpickle tokenizer: {}", e)))?;
+        Ok(())
+    }
+
+    /// Rebuild via the class's own `__new__` (no-arg constructor), then restore
+    /// state through `__setstate__`.
+    fn __reduce__(slf: &Bound<Self>) -> PyResult<(PyObject, (), PyObject)> {
+        let py = slf.py();
+        let cls = slf.get_type().unbind();
+        let state = slf.borrow().__getstate__(py)?.unbind().into();
+        Ok((cls, (), state))
+    }
+}
+
+#[pyclass]
+struct VietnameseSubwordEncoderPy {
+    encoder: SubwordEncoder,
+}
+
+#[pymethods]
+impl VietnameseSubwordEncoderPy {
+    #[staticmethod]
+    fn from_vocab(vocab_path: &str) -> PyResult<Self> {
+        let encoder = SubwordEncoder::from_vocab(vocab_path)
+            .map_err(|e| PyValueError::new_err(format!("failed to load vocab: {}", e)))?;
+        Ok(Self { encoder })
+    }
+
+    #[staticmethod]
+    fn from_vocab_and_merges(vocab_path: &str, merges_path: &str) -> PyResult<Self> {
+        let encoder = SubwordEncoder::from_vocab_and_merges(
+            vocab_path,
+            Some(merges_path),
+            crate::subword::EncodingMethod::Bpe,
+        )
+        .map_err(|e| PyValueError::new_err(format!("failed to load vocab/merges: {}", e)))?;
+        Ok(Self { encoder })
+    }
+
+    fn encode(&self, text: &str) -> (Vec<String>, Vec<u32>, Vec<(usize, usize)>) {
+        let encoding = self.encoder.encode(text);
+        (encoding.tokens, encoding.ids, encoding.offsets)
+    }
 }
 
 #[pymodule]
 fn _parrotnlp(_py: Python, m: &Bound<PyModule>) -> PyResult<()> {
     m.add_class::<VietnameseWordSegmenterPy>()?;
     m.add_class::<VietnameseTokenizerPy>()?;
+    m.add_class::<VietnameseSubwordEncoderPy>()?;
     Ok(())
 }
@@ -0,0 +1,3 @@
+pub mod subword_encoder;
+
+pub use subword_encoder::{Encoding, EncodingMethod, SubwordEncoder};
@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::word_tokenize::{Token, TokenType, VietnameseTokenizer};
+
+/// Which subword algorithm a `SubwordEncoder` applies to each tokenized word.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncodingMethod {
+    WordPiece,
+    Bpe,
+}
+
+/// A transformer-ready encoding of a piece of text: the subword pieces, their
+/// vocabulary ids, and each piece's byte span in the text passed to `encode`
+/// (after normalization, if the underlying tokenizer applies any — the same
+/// convention `Token::start`/`Token::end` use).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Encoding {
+    pub tokens: Vec<String>,
+    pub ids: Vec<u32>,
+    pub offsets: Vec<(usize, usize)>,
+}
+
+/// Splits tokenized Vietnamese text into model-ready subword IDs, the way
+/// rust_tokenizers' `BertTokenizer`/`Gpt2Tokenizer` sit in front of
+/// rust-bert.
+///
+/// Built on top of `VietnameseTokenizer`: regex tokenization (and, through
+/// it, syllable-level word boundaries) runs first, then each word-level
+/// token is split into subwords with either WordPiece (greedy
+/// longest-match-first) or BPE (iterative highest-priority merge). Pieces
+/// are cut at character boundaries of the tokenizer's NFC-normalized text,
+/// where a Vietnamese vowel and its diacritics are one precomposed
+/// character, so a piece never separates a diacritic from its vowel.
+pub struct SubwordEncoder {
+    method: EncodingMethod,
+    vocab: HashMap<String, u32>,
+    merge_ranks: HashMap<(String, String), usize>,
+    continuation_prefix: String,
+    unk_token: String,
+    tokenizer: VietnameseTokenizer,
+    /// Token types (e.g. `Url`, `Email`, `Number`) emitted as a single
+    /// atomic piece instead of being split into subwords. Empty by default.
+    atomic_token_types: Vec<TokenType>,
+}
+
+impl SubwordEncoder {
+    /// Load a WordPiece vocabulary from a HuggingFace-style `vocab.txt` (one
+    /// token per line, id = line number).
+    pub fn from_vocab<P: AsRef<Path>>(vocab_path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::from_vocab_and_merges::<P, P>(vocab_path, None, EncodingMethod::WordPiece)
+    }
+
+    /// Load a vocabulary and, for BPE, a `merges.txt` ranking adjacent-pair
+    /// merges by line order (lower = applied first).
+    pub fn from_vocab_and_merges<P: AsRef<Path>, Q: AsRef<Path>>(
+        vocab_path: P,
+        merges_path: Option<Q>,
+        method: EncodingMethod,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let vocab_text = fs::read_to_string(vocab_path)?;
+        let vocab: HashMap<String, u32> = vocab_text
+            .lines()
+            .enumerate()
+            .map(|(id, token)| (token.trim().to_string(), id as u32))
+            .collect();
+
+        let mut merge_ranks = HashMap::new();
+        if let Some(path) = merges_path {
+            let merges_text = fs::read_to_string(path)?;
+            for (rank, line) in merges_text.lines().enumerate() {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                if let Some((left, right)) = line.split_once(' ') {
+                    merge_ranks.insert((left.to_string(), right.to_string()), rank);
+                }
+            }
+        }
+
+        Ok(Self {
+            method,
+            vocab,
+            merge_ranks,
+            continuation_prefix: "##".to_string(),
+            unk_token: "[UNK]".to_string(),
+            tokenizer: VietnameseTokenizer::new(),
+            atomic_token_types: Vec::new(),
+        })
+    }
+
+    /// Override the continuation-piece prefix (HuggingFace BERT uses `##`).
+    pub fn with_continuation_prefix(mut self, prefix: &str) -> Self {
+        self.continuation_prefix = prefix.to_string();
+        self
+    }
+
+    /// Override the placeholder emitted for characters/words the vocabulary
+    /// doesn't cover (HuggingFace BERT uses `[UNK]`).
+    pub fn with_unk_token(mut self, unk_token: &str) -> Self {
+        self.unk_token = unk_token.to_string();
+        self
+    }
+
+    /// Keep tokens of the given types atomic (never split into subwords, and
+    /// looked up in the vocabulary as-is), e.g. so a URL or email address
+    /// isn't shredded into meaningless pieces.
+    pub fn with_atomic_token_types(mut self, token_types: Vec<TokenType>) -> Self {
+        self.atomic_token_types = token_types;
+        self
+    }
+
+    /// Tokenize `text`, then split each word-level token into subwords,
+    /// returning pieces, ids, and each piece's byte span in the tokenizer's
+    /// normalized form of `text` (see `Encoding::offsets`).
+    pub fn encode(&self, text: &str) -> Encoding {
+        let (word_tokens, normalized_source) = self.tokenizer.tokenize_with_offsets(text, true);
+        // `Token::start`/`end` are character offsets into `normalized_source`,
+        // not `text` itself — normalization is not always character-count
+        // preserving, so indexing a byte table built from `text` can point at
+        // the wrong bytes (or panic) whenever it isn't. See `tokenize_with_offsets`.
+        let char_byte_offsets = char_byte_offsets(&normalized_source);
+
+        let mut tokens = Vec::new();
+        let mut ids = Vec::new();
+        let mut offsets = Vec::new();
+
+        for word_token in &word_tokens {
+            let mut char_cursor = word_token.start;
+            for (piece, char_len) in self.encode_word_token(word_token) {
+                let piece_end = char_cursor + char_len;
+                ids.push(*self.vocab.get(&piece).unwrap_or(&self.unk_id()));
+                tokens.push(piece);
+                offsets.push((char_byte_offsets[char_cursor], char_byte_offsets[piece_end]));
+                char_cursor = piece_end;
+            }
+        }
+
+        Encoding { tokens, ids, offsets }
+    }
+
+    /// Split one word-level `Token` into `(piece, char length consumed)`
+    /// pairs, keeping atomic token types whole.
+    fn encode_word_token(&self, token: &Token) -> Vec<(String, usize)> {
+        if self.atomic_token_types.contains(&token.token_type) {
+            return vec![(token.text.clone(), token.text.chars().count())];
+        }
+
+        match self.method {
+            EncodingMethod::WordPiece => self.wordpiece_encode_word(&token.text),
+            EncodingMethod::Bpe => self.bpe_encode_word(&token.text),
+        }
+    }
+
+    /// Encode each of `texts` independently.
+    pub fn encode_batch(&self, texts: &[&str]) -> Vec<Encoding> {
+        texts.iter().map(|text| self.encode(text)).collect()
+    }
+
+    fn unk_id(&self) -> u32 {
+        *self.vocab.get(&self.unk_token).unwrap_or(&0)
+    }
+
+    /// Greedy longest-match-first split: repeatedly take the longest prefix of
+    /// the remaining characters that is present in the vocab, prefixing
+    /// continuation pieces with `##`. If any piece fails to match, the whole
+    /// word is emitted as a single `[UNK]`. Each returned piece carries the
+    /// number of the word's characters it consumed, so `[UNK]` still reports
+    /// the full word length rather than the placeholder's own length.
+    fn wordpiece_encode_word(&self, word: &str) -> Vec<(String, usize)> {
+        let chars: Vec<char> = word.chars().collect();
+        let mut pieces = Vec::new();
+        let mut start = 0;
+
+        while start < chars.len() {
+            let mut end = chars.len();
+            let mut matched = None;
+
+            while end > start {
+                let candidate: String = chars[start..end].iter().collect();
+                let candidate = if start > 0 {
+                    format!("{}{}", self.continuation_prefix, candidate)
+                } else {
+                    candidate
+                };
+                if self.vocab.contains_key(&candidate) {
+                    matched = Some(candidate);
+                    break;
+                }
+                end -= 1;
+            }
+
+            match matched {
+                Some(piece) => {
+                    pieces.push((piece, end - start));
+                    start = end;
+                }
+                None => return vec![(self.unk_token.clone(), chars.len())],
+            }
+        }
+
+        pieces
+    }
+
+    /// Start from individual characters and repeatedly merge the adjacent pair
+    /// with the lowest (highest-priority) rank in `merge_ranks` until no
+    /// mergeable pair remains. Each returned piece carries the number of
+    /// characters it was merged from, even when the piece itself is replaced
+    /// with the `[UNK]` placeholder.
+    fn bpe_encode_word(&self, word: &str) -> Vec<(String, usize)> {
+        let mut symbols: Vec<String> = word.chars().map(|c| c.to_string()).collect();
+
+        loop {
+            let mut best: Option<(usize, usize)> = None; // (rank, position)
+            for i in 0..symbols.len().saturating_sub(1) {
+                let pair = (symbols[i].clone(), symbols[i + 1].clone());
+                if let Some(&rank) = self.merge_ranks.get(&pair) {
+                    if best.map_or(true, |(best_rank, _)| rank < best_rank) {
+                        best = Some((rank, i));
+                    }
+                }
+            }
+
+            match best {
+                Some((_, i)) => {
+                    let merged = format!("{}{}", symbols[i], symbols[i + 1]);
+                    symbols.splice(i..=i + 1, [merged]);
+                }
+                None => break,
+            }
+        }
+
+        symbols
+            .into_iter()
+            .map(|symbol| {
+                let char_len = symbol.chars().count();
+                if self.vocab.contains_key(&symbol) {
+                    (symbol, char_len)
+                } else {
+                    (self.unk_token.clone(), char_len)
+                }
+            })
+            .collect()
+    }
+}
+
+/// Byte offset of every character position in `text`, indexable by char
+/// index (with a final sentinel entry at `text.len()` for an end-exclusive
+/// span ending at the last character).
+fn char_byte_offsets(text: &str) -> Vec<usize> {
+    let mut offsets: Vec<usize> = text.char_indices().map(|(byte_offset, _)| byte_offset).collect();
+    offsets.push(text.len());
+    offsets
+}
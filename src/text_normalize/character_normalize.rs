@@ -1,4 +1,5 @@
 use unicode_normalization::UnicodeNormalization;
+use crate::text_normalize::html_entities::decode_html_entities;
 use crate::text_normalize::text_normalizer::get_normalizer;
 
 /// Normalize characters in text using the character map
@@ -23,3 +24,17 @@ pub fn normalize_characters_in_text(text: &str) -> String {
     let text = utf8_normalize(text);
     character_normalize(&text)
 }
+
+/// Like `normalize_characters_in_text`, but with an opt-in HTML-entity
+/// decoding pass (`&amp;`, `&#7879;`, `&#x1EC7;`, `&ocirc;`, ...) ahead of
+/// `utf8_normalize`, analogous to `token_normalize`'s
+/// `use_character_normalize` flag: scraped web text arrives with entities
+/// that would otherwise pass straight through and corrupt tokenization.
+pub fn normalize_characters_in_text_with_options(text: &str, decode_entities: bool) -> String {
+    let text = if decode_entities {
+        decode_html_entities(text)
+    } else {
+        text.to_string()
+    };
+    normalize_characters_in_text(&text)
+}
@@ -1,13 +1,26 @@
 pub mod text_normalizer;
 pub mod character_normalize;
+pub mod html_entities;
 pub mod token_normalize;
+pub mod transcode;
 
 pub use text_normalizer::{TextNormalizer, load_rules};
-pub use character_normalize::{character_normalize, utf8_normalize, normalize_characters_in_text};
+pub use character_normalize::{character_normalize, utf8_normalize, normalize_characters_in_text, normalize_characters_in_text_with_options};
+pub use html_entities::decode_html_entities;
 pub use token_normalize::token_normalize;
+pub use transcode::{transcode_to_unicode, Encoding, EncodingDetector};
 
 use crate::word_tokenize::VietnameseTokenizer;
 
+/// Transcode `bytes` out of a legacy Vietnamese encoding (see
+/// `transcode::Encoding`) before running the rest of the normalization
+/// pipeline over it, so scraped/legacy corpora that aren't Unicode at all
+/// normalize correctly instead of coming out mangled. `hint` skips
+/// detection when the source encoding is already known.
+pub fn normalize_legacy_text(bytes: &[u8], hint: Option<Encoding>) -> String {
+    normalize_characters_in_text(&transcode_to_unicode(bytes, hint))
+}
+
 /// Text normalization function
 /// 
 /// # Arguments
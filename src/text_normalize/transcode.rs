@@ -0,0 +1,248 @@
+use crate::word_tokenize::tone_normalize::compose_tone;
+
+/// A legacy (pre-Unicode) Vietnamese text encoding this module can transcode
+/// from, detected heuristically by `EncodingDetector::detect` when the
+/// caller doesn't already know the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// Already Unicode; no transcoding needed.
+    Unicode,
+    /// TCVN3 / ABC: a single-byte, Windows-1258-adjacent encoding that packs
+    /// Vietnamese letters into the high byte range (0x80-0xFF), so it isn't
+    /// valid UTF-8 on its own.
+    Tcvn3,
+    /// VNI: ASCII letters followed by a digit per diacritic/tone (â = a6,
+    /// ư = u7, ă = a8, đ = d9; tones sắc = 1, huyền = 2, hỏi = 3, ngã = 4,
+    /// nặng = 5).
+    Vni,
+    /// VIQR: ASCII letters followed by ASCII punctuation per diacritic/tone
+    /// (^ circumflex, + horn, ( breve, dd -> đ; tones ' sắc, ` huyền, ? hỏi,
+    /// ~ ngã, . nặng).
+    Viqr,
+}
+
+/// `(plain base vowel, marker, modified base vowel)`, e.g. `('a', '8', 'ă')`
+/// for VNI or `('a', '(', 'ă')` for VIQR. The modified base is itself a valid
+/// `tone_normalize::VOWEL_GROUPS` entry, so tone composition works the same
+/// whether or not a modifier fired.
+type ModifierTable = &'static [(char, char, char)];
+/// `(marker, tone_normalize tone index)`; the legacy scheme's own tone
+/// numbering (VNI: 1=sắc, 2=huyền, ...) doesn't match `VOWEL_GROUPS`'s column
+/// order (1=huyền, 2=sắc, ...), so this table translates between them.
+type ToneTable = &'static [(char, u8)];
+
+const VNI_MODIFIERS: ModifierTable = &[
+    ('a', '6', 'â'), ('a', '8', 'ă'),
+    ('e', '6', 'ê'),
+    ('o', '6', 'ô'), ('o', '7', 'ơ'),
+    ('u', '7', 'ư'),
+];
+const VNI_TONES: ToneTable = &[('1', 2), ('2', 1), ('3', 3), ('4', 4), ('5', 5)];
+
+const VIQR_MODIFIERS: ModifierTable = &[
+    ('a', '^', 'â'), ('a', '(', 'ă'),
+    ('e', '^', 'ê'),
+    ('o', '^', 'ô'), ('o', '+', 'ơ'),
+    ('u', '+', 'ư'),
+];
+const VIQR_TONES: ToneTable = &[('\'', 2), ('`', 1), ('?', 3), ('~', 4), ('.', 5)];
+
+/// Partial TCVN3 (ABC) single-byte -> Unicode table: the bundled entries
+/// cover the plain modified-vowel letters and `đ`/`Đ`, which is enough to
+/// unmangle the most common legacy bytes; a full byte-for-byte table (every
+/// precomposed vowel+tone combination has its own byte in real TCVN3) would
+/// need to be extended here from a verified corpus before relying on this
+/// for anything beyond best-effort cleanup.
+const TCVN3_BYTE_MAP: &[(u8, char)] = &[
+    (0xB0, 'ă'), (0xA8, 'Ă'),
+    (0xE2, 'â'), (0xC2, 'Â'),
+    (0xEA, 'ê'), (0xCA, 'Ê'),
+    (0xF4, 'ô'), (0xD4, 'Ô'),
+    (0xEF, 'ơ'), (0xCF, 'Ơ'),
+    (0xF7, 'ư'), (0xD7, 'Ư'),
+    (0xF0, 'đ'), (0xD0, 'Đ'),
+];
+
+fn lookup_modifier(table: ModifierTable, base: char, marker: char) -> Option<char> {
+    table.iter().find(|&&(b, m, _)| b == base && m == marker).map(|&(_, _, modified)| modified)
+}
+
+fn lookup_tone(table: ToneTable, marker: char) -> Option<u8> {
+    table.iter().find(|&&(m, _)| m == marker).map(|&(_, t)| t)
+}
+
+fn is_base_vowel(c: char) -> bool {
+    matches!(c.to_ascii_lowercase(), 'a' | 'e' | 'i' | 'o' | 'u' | 'y')
+}
+
+/// Heuristically detects and transcodes legacy Vietnamese text encodings,
+/// mirroring the charset-sniffing idea behind eml-codec's `guess_charset`.
+pub struct EncodingDetector;
+
+impl EncodingDetector {
+    /// Guess `bytes`'s encoding from its byte/character signature: invalid
+    /// UTF-8 means it's almost certainly TCVN3 (a single-byte encoding);
+    /// valid UTF-8 with a base vowel directly followed by a VNI
+    /// modifier/tone digit, or by a VIQR modifier/tone punctuation mark
+    /// (including the `dd`/`DD` spelling of đ), flags VNI or VIQR
+    /// respectively. Anything else is assumed to already be Unicode.
+    pub fn detect(bytes: &[u8]) -> Encoding {
+        let Ok(text) = std::str::from_utf8(bytes) else {
+            return Encoding::Tcvn3;
+        };
+
+        if has_signature(text, VNI_MODIFIERS, VNI_TONES, |_| false) {
+            Encoding::Vni
+        } else if has_signature(text, VIQR_MODIFIERS, VIQR_TONES, |lower| lower == 'd') {
+            Encoding::Viqr
+        } else {
+            Encoding::Unicode
+        }
+    }
+}
+
+/// Whether `text` contains a base vowel immediately followed by one of
+/// `modifiers`'/`tones`'s markers, or (for VIQR's `dd` -> đ spelling)
+/// `extra_doubled_consonant` fires on a doubled consonant letter.
+fn has_signature(text: &str, modifiers: ModifierTable, tones: ToneTable, extra_doubled_consonant: impl Fn(char) -> bool) -> bool {
+    let chars: Vec<char> = text.chars().collect();
+    chars.windows(2).any(|w| {
+        let lower = w[0].to_ascii_lowercase();
+        if is_base_vowel(lower) {
+            return lookup_modifier(modifiers, lower, w[1]).is_some() || lookup_tone(tones, w[1]).is_some();
+        }
+        extra_doubled_consonant(lower) && w[1].to_ascii_lowercase() == lower
+    })
+}
+
+/// Decode `text` out of `modifiers`/`tones`'s marker scheme (VNI or VIQR):
+/// scan for a base vowel, consume an optional modifier marker then an
+/// optional tone marker right after it, and compose the result via
+/// `tone_normalize::compose_tone`. `doubled_consonant_marker` says whether
+/// the char after a `d` completes VNI's `d9` / VIQR's `dd` spelling of đ,
+/// which isn't a vowel and so falls outside the marker-table shape above.
+fn transcode_marker_scheme(
+    text: &str,
+    modifiers: ModifierTable,
+    tones: ToneTable,
+    doubled_consonant_marker: impl Fn(char) -> bool,
+) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut output = String::with_capacity(text.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let lower = c.to_ascii_lowercase();
+        let is_upper = c.is_ascii_uppercase();
+
+        if lower == 'd' && chars.get(i + 1).map(|m| doubled_consonant_marker(*m)).unwrap_or(false) {
+            output.push(if is_upper { 'Đ' } else { 'đ' });
+            i += 2;
+            continue;
+        }
+
+        if is_base_vowel(lower) {
+            let mut base = lower;
+            let mut j = i + 1;
+
+            if let Some(&marker) = chars.get(j) {
+                if let Some(modified) = lookup_modifier(modifiers, base, marker) {
+                    base = modified;
+                    j += 1;
+                }
+            }
+
+            let mut tone = 0u8;
+            if let Some(&marker) = chars.get(j) {
+                if let Some(t) = lookup_tone(tones, marker) {
+                    tone = t;
+                    j += 1;
+                }
+            }
+
+            if base != lower || tone != 0 {
+                output.push(compose_tone(base, tone, is_upper));
+                i = j;
+                continue;
+            }
+        }
+
+        output.push(c);
+        i += 1;
+    }
+
+    output
+}
+
+fn transcode_tcvn3(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|&byte| {
+            TCVN3_BYTE_MAP
+                .iter()
+                .find(|&&(b, _)| b == byte)
+                .map(|&(_, unicode)| unicode)
+                .unwrap_or(byte as char)
+        })
+        .collect()
+}
+
+/// Transcode `bytes` to Unicode, using `hint` as the source encoding if
+/// given or `EncodingDetector::detect`ing it otherwise, then wire this as an
+/// optional pre-pass before the rest of the normalization pipeline
+/// (`utf8_normalize`/`character_normalize`) runs, so scraped or legacy
+/// corpora normalize correctly instead of coming out mangled.
+pub fn transcode_to_unicode(bytes: &[u8], hint: Option<Encoding>) -> String {
+    match hint.unwrap_or_else(|| EncodingDetector::detect(bytes)) {
+        Encoding::Unicode => String::from_utf8_lossy(bytes).into_owned(),
+        Encoding::Tcvn3 => transcode_tcvn3(bytes),
+        Encoding::Vni => transcode_marker_scheme(&String::from_utf8_lossy(bytes), VNI_MODIFIERS, VNI_TONES, |m| m == '9'),
+        Encoding::Viqr => transcode_marker_scheme(&String::from_utf8_lossy(bytes), VIQR_MODIFIERS, VIQR_TONES, |m| m == 'd'),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_vni_from_digit_tone_marker() {
+        assert_eq!(EncodingDetector::detect("Tie61ng Vie65t".as_bytes()), Encoding::Vni);
+    }
+
+    #[test]
+    fn test_detect_viqr_from_punctuation_marker() {
+        assert_eq!(EncodingDetector::detect("Tie^'ng Vie^.t".as_bytes()), Encoding::Viqr);
+    }
+
+    #[test]
+    fn test_detect_already_unicode_text() {
+        assert_eq!(EncodingDetector::detect("Tiếng Việt".as_bytes()), Encoding::Unicode);
+    }
+
+    #[test]
+    fn test_transcode_vni_composes_modifier_and_tone() {
+        assert_eq!(transcode_to_unicode("Vie65t Nam".as_bytes(), Some(Encoding::Vni)), "Việt Nam");
+    }
+
+    #[test]
+    fn test_transcode_vni_composes_standalone_dia() {
+        assert_eq!(transcode_to_unicode("d9a81t".as_bytes(), Some(Encoding::Vni)), "đắt");
+    }
+
+    #[test]
+    fn test_transcode_viqr_composes_modifier_and_tone() {
+        assert_eq!(transcode_to_unicode("Vie^.t Nam".as_bytes(), Some(Encoding::Viqr)), "Việt Nam");
+    }
+
+    #[test]
+    fn test_transcode_viqr_composes_dd_to_d_with_stroke() {
+        assert_eq!(transcode_to_unicode("ddaát".as_bytes(), Some(Encoding::Viqr)), "đaát");
+    }
+
+    #[test]
+    fn test_transcode_unicode_passthrough() {
+        assert_eq!(transcode_to_unicode("Tiếng Việt".as_bytes(), Some(Encoding::Unicode)), "Tiếng Việt");
+    }
+}
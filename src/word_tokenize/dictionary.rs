@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::word_tokenize::word_tokenize::{Token, TokenType};
+
+/// A node in the word-dictionary trie. This is a simplified, `HashMap`-backed
+/// trie; a production system would use a double-array trie (as cedarwood does
+/// for jieba) for cache-friendly, allocation-free lookups.
+#[derive(Debug, Default)]
+struct TrieNode {
+    children: HashMap<String, TrieNode>,
+    freq: Option<u64>,
+}
+
+/// A frequency-weighted dictionary of Vietnamese words (each a sequence of one
+/// or more syllables) used to drive maximum-probability segmentation.
+#[derive(Debug, Default)]
+pub struct WordDictionary {
+    root: TrieNode,
+    total_freq: u64,
+    max_word_syllables: usize,
+}
+
+impl WordDictionary {
+    pub fn new() -> Self {
+        Self {
+            root: TrieNode::default(),
+            total_freq: 0,
+            max_word_syllables: 1,
+        }
+    }
+
+    /// Load a dictionary from a `word\tfreq` per-line text file, where `word`
+    /// is a space-joined sequence of syllables (e.g. `nghiên cứu\t1500`).
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let text = fs::read_to_string(path)?;
+        let mut dict = Self::new();
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut parts = line.rsplitn(2, '\t');
+            let freq_str = parts.next().unwrap_or("1");
+            let word = parts.next().unwrap_or(line);
+            let freq: u64 = freq_str.trim().parse().unwrap_or(1);
+            dict.insert(word.trim(), freq);
+        }
+        Ok(dict)
+    }
+
+    /// Insert a word with the given frequency, overwriting the previous
+    /// frequency for that word if already present.
+    pub fn insert(&mut self, word: &str, freq: u64) {
+        let syllables: Vec<&str> = word.split_whitespace().collect();
+        if syllables.is_empty() {
+            return;
+        }
+
+        let mut node = &mut self.root;
+        for syllable in &syllables {
+            node = node
+                .children
+                .entry(syllable.to_string())
+                .or_insert_with(TrieNode::default);
+        }
+
+        if node.freq.is_none() {
+            self.total_freq += freq;
+        } else {
+            self.total_freq = self.total_freq - node.freq.unwrap() + freq;
+        }
+        node.freq = Some(freq);
+        self.max_word_syllables = self.max_word_syllables.max(syllables.len());
+    }
+
+    /// Add user-supplied words at a high frequency so they win ties against
+    /// ordinary dictionary entries, mirroring `with_fixed_words`.
+    pub fn add_user_words(&mut self, user_words: &[String]) {
+        const USER_WORD_FREQ: u64 = 1_000_000;
+        for word in user_words {
+            self.insert(word, USER_WORD_FREQ);
+        }
+    }
+
+    pub fn max_word_syllables(&self) -> usize {
+        self.max_word_syllables
+    }
+
+    /// Frequency of the word formed by joining `syllables` with spaces, if any.
+    fn freq_of(&self, syllables: &[&str]) -> Option<u64> {
+        let mut node = &self.root;
+        for syllable in syllables {
+            node = node.children.get(*syllable)?;
+        }
+        node.freq
+    }
+
+    fn total_freq(&self) -> u64 {
+        self.total_freq.max(1)
+    }
+}
+
+/// Log-probability assigned (in place of a real one) to a syllable with no
+/// dictionary coverage at all, so segmentation always makes progress.
+const UNKNOWN_SYLLABLE_LOG_PROB: f64 = -9.0;
+
+/// Run right-to-left dynamic programming over `syllables` to find the
+/// maximum-probability segmentation into dictionary words, merging the
+/// surviving spans into `" "`-joined words.
+///
+/// `route[i]` holds the best cumulative log-probability achievable from
+/// syllable `i` to the end, computed as
+/// `route[i] = max over j of log(freq(i..j) / total) + route[j]`,
+/// restricted to `j - i <= dictionary.max_word_syllables()`. Unknown
+/// syllables fall back to a fixed penalty so every position is reachable.
+pub fn segment_with_dictionary(dictionary: &WordDictionary, syllables: &[String]) -> Vec<String> {
+    let n = syllables.len();
+    if n == 0 {
+        return Vec::new();
+    }
+
+    let max_len = dictionary.max_word_syllables().max(1);
+    let total = dictionary.total_freq() as f64;
+
+    let mut best_score = vec![f64::NEG_INFINITY; n + 1];
+    let mut best_next = vec![n; n + 1];
+    best_score[n] = 0.0;
+
+    for i in (0..n).rev() {
+        let max_j = (i + max_len).min(n);
+        for j in (i + 1)..=max_j {
+            let slice: Vec<&str> = syllables[i..j].iter().map(|s| s.as_str()).collect();
+            let log_prob = match (dictionary.freq_of(&slice), j - i) {
+                (Some(freq), _) => (freq as f64 / total).ln(),
+                (None, 1) => UNKNOWN_SYLLABLE_LOG_PROB,
+                (None, _) => continue,
+            };
+            let score = log_prob + best_score[j];
+            // Prefer higher score; on a tie prefer fewer words, i.e. the
+            // larger `j` (a longer merged span covers more ground in one step).
+            if score > best_score[i] || (score == best_score[i] && j > best_next[i]) {
+                best_score[i] = score;
+                best_next[i] = j;
+            }
+        }
+    }
+
+    let mut words = Vec::new();
+    let mut i = 0;
+    while i < n {
+        let j = best_next[i];
+        words.push(syllables[i..j].join(" "));
+        i = j;
+    }
+    words
+}
+
+/// Like `segment_with_dictionary`, but runs over tagged `Token`s instead of
+/// bare syllable strings so a compound word is never merged across a
+/// non-`Word` token: punctuation, URLs, numbers, emoji, etc. keep their own
+/// `TokenType` and act as hard boundaries, passing through unchanged between
+/// the dictionary-segmentation runs over the `Word` tokens either side of them.
+pub fn segment_tokens_with_dictionary(dictionary: &WordDictionary, tokens: &[Token]) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut run: Vec<String> = Vec::new();
+
+    for token in tokens {
+        if token.token_type == TokenType::Word {
+            run.push(token.text.clone());
+            continue;
+        }
+
+        if !run.is_empty() {
+            words.extend(segment_with_dictionary(dictionary, &run));
+            run.clear();
+        }
+        words.push(token.text.clone());
+    }
+    if !run.is_empty() {
+        words.extend(segment_with_dictionary(dictionary, &run));
+    }
+
+    words
+}
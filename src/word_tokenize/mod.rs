@@ -1,8 +1,20 @@
 pub mod word_tokenize;
+pub mod dictionary;
+pub mod tone_normalize;
+pub mod spellcheck;
+pub mod tagger;
+pub mod trie_segmenter;
 
 pub use word_tokenize::{
     VietnameseWordSegmenter,
     VietnameseTokenizer,
     Token,
     TokenType,
+    TokenizerOptions,
+    fold,
 };
+pub use dictionary::WordDictionary;
+pub use spellcheck::SpellChecker;
+pub use tagger::VietnameseTagger;
+pub use tone_normalize::NormalizationMode;
+pub use trie_segmenter::TrieSegmenter;
@@ -0,0 +1,429 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::word_tokenize::tone_normalize::canonicalize_for_matching;
+use crate::word_tokenize::word_tokenize::{Token, TokenType, VietnameseTokenizer};
+
+/// Lowercase Vietnamese alphabet used to generate replacement/insertion
+/// candidates during spelling correction, including every tone-marked vowel
+/// so a missing or wrong diacritic — the dominant real-world Vietnamese
+/// typo — is always a single substitution away.
+const SPELLCHECK_ALPHABET: &str = "abcdefghijklmnopqrstuvxyz\
+    àáảãạăằắẳẵặâầấẩẫậ\
+    đ\
+    èéẻẽẹêềếểễệ\
+    ìíỉĩị\
+    òóỏõọôồốổỗộơờớởỡợ\
+    ùúủũụưừứửữự\
+    ỳýỷỹỵ";
+
+/// A dictionary-backed Vietnamese spell checker, modeled on the
+/// Hunspell/nlprule approach of a plain word-frequency list plus bounded
+/// edit-distance candidate generation. Candidates are generated with the
+/// classic Norvig "did you mean" edits (insertion, deletion, substitution,
+/// transposition) restricted to `SPELLCHECK_ALPHABET`, then ranked by
+/// dictionary frequency.
+pub struct SpellChecker {
+    tokenizer: VietnameseTokenizer,
+    dictionary: HashMap<String, u64>,
+    /// Symmetric-delete index: every dictionary word's deletion variants (up
+    /// to `MAX_DELETE_DISTANCE` deletions) mapped back to the words that
+    /// produced them, so `correct` only has to generate deletions of the
+    /// *query*, not of the whole dictionary, at lookup time.
+    delete_index: HashMap<String, Vec<String>>,
+    /// Prefix trie over the same dictionary words, for `suggest_completions`.
+    prefix_trie: PrefixTrieNode,
+}
+
+/// Maximum number of character deletions the symmetric-delete index and
+/// `correct` will bridge between a misspelling and a dictionary word. Two
+/// catches a missing/extra diacritic plus one ordinary typo, matching
+/// `SPELLCHECK_ALPHABET`'s Norvig-edit counterpart in spirit.
+const MAX_DELETE_DISTANCE: usize = 2;
+
+#[derive(Default)]
+struct PrefixTrieNode {
+    children: HashMap<char, PrefixTrieNode>,
+    word: Option<String>,
+}
+
+impl SpellChecker {
+    pub fn new() -> Self {
+        Self {
+            tokenizer: VietnameseTokenizer::new(),
+            dictionary: HashMap::new(),
+            delete_index: HashMap::new(),
+            prefix_trie: PrefixTrieNode::default(),
+        }
+    }
+
+    /// Load a `word<TAB>freq` per-line frequency list (the same format as
+    /// `WordDictionary::load_from_file`), canonicalizing each entry so
+    /// lookups are insensitive to tone-mark placement style and lookalike
+    /// codepoints.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let text = fs::read_to_string(path)?;
+        let mut checker = Self::new();
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut parts = line.rsplitn(2, '\t');
+            let freq_str = parts.next().unwrap_or("1");
+            let word = parts.next().unwrap_or(line);
+            let freq: u64 = freq_str.trim().parse().unwrap_or(1);
+            checker.insert(word.trim(), freq);
+        }
+        Ok(checker)
+    }
+
+    /// Insert (or overwrite the frequency of) a dictionary entry.
+    pub fn insert(&mut self, word: &str, freq: u64) {
+        let canonical = canonicalize_for_matching(&word.to_lowercase());
+        self.index_deletions(&canonical);
+        self.index_prefix(&canonical);
+        self.dictionary.insert(canonical, freq);
+    }
+
+    pub fn is_known(&self, word: &str) -> bool {
+        self.dictionary.contains_key(&canonicalize_for_matching(&word.to_lowercase()))
+    }
+
+    fn index_deletions(&mut self, canonical: &str) {
+        for variant in delete_variants(canonical, MAX_DELETE_DISTANCE) {
+            let bucket = self.delete_index.entry(variant).or_insert_with(Vec::new);
+            if !bucket.iter().any(|w| w == canonical) {
+                bucket.push(canonical.to_string());
+            }
+        }
+    }
+
+    fn index_prefix(&mut self, canonical: &str) {
+        let mut node = &mut self.prefix_trie;
+        for c in canonical.chars() {
+            node = node.children.entry(c).or_insert_with(PrefixTrieNode::default);
+        }
+        node.word = Some(canonical.to_string());
+    }
+
+    /// Symmetric-delete correction: generate `word`'s own deletion variants
+    /// (up to `MAX_DELETE_DISTANCE`), union the dictionary words indexed
+    /// under each variant, then rank the union by true Levenshtein distance
+    /// to `word` (ties broken by dictionary frequency, highest first).
+    /// Returns an empty list for words already in the dictionary. Cheaper
+    /// than `suggestions_for` once the dictionary is large, since candidate
+    /// generation only touches `word` rather than every possible edit of it.
+    pub fn correct(&self, word: &str) -> Vec<String> {
+        let canonical = canonicalize_for_matching(&word.to_lowercase());
+        if self.dictionary.contains_key(&canonical) {
+            return Vec::new();
+        }
+
+        let mut candidates: HashSet<&String> = HashSet::new();
+        for variant in delete_variants(&canonical, MAX_DELETE_DISTANCE) {
+            if let Some(bucket) = self.delete_index.get(&variant) {
+                candidates.extend(bucket.iter());
+            }
+        }
+
+        let mut ranked: Vec<(&String, usize)> = candidates
+            .into_iter()
+            .filter_map(|candidate| {
+                let distance = levenshtein_distance(&canonical, candidate);
+                (distance <= MAX_DELETE_DISTANCE).then_some((candidate, distance))
+            })
+            .collect();
+
+        ranked.sort_by(|a, b| {
+            a.1.cmp(&b.1)
+                .then_with(|| self.dictionary[b.0].cmp(&self.dictionary[a.0]))
+                .then_with(|| a.0.cmp(b.0))
+        });
+
+        ranked
+            .into_iter()
+            .map(|(candidate, _)| capitalize_like(word, candidate))
+            .collect()
+    }
+
+    /// Autocomplete: every dictionary word beginning with `prefix`, ranked by
+    /// frequency (highest first). Distinct from `suggest`, which flags and
+    /// corrects out-of-vocabulary tokens in running text — this is a pure
+    /// prefix lookup for "as-you-type" completion over the same dictionary.
+    pub fn suggest_completions(&self, prefix: &str) -> Vec<String> {
+        let canonical_prefix = canonicalize_for_matching(&prefix.to_lowercase());
+        let mut node = &self.prefix_trie;
+        for c in canonical_prefix.chars() {
+            match node.children.get(&c) {
+                Some(child) => node = child,
+                None => return Vec::new(),
+            }
+        }
+
+        let mut completions = Vec::new();
+        collect_words(node, &mut completions);
+        completions.sort_by(|a, b| {
+            self.dictionary
+                .get(b)
+                .cmp(&self.dictionary.get(a))
+                .then_with(|| a.cmp(b))
+        });
+        completions
+    }
+
+    /// Tokenize `text` and return, for every `TokenType::Word` token absent
+    /// from the dictionary, that token paired with correction candidates
+    /// within edit distance 1-2, ranked by dictionary frequency (highest
+    /// first). Distance-2 candidates are only generated when no distance-1
+    /// candidate is known, matching Norvig's `correction()`. Reuses the
+    /// tokenizer's own normalization (`canonicalize_for_matching`) so
+    /// diacritic-less input like "Ha Noi" is compared, and suggested back,
+    /// against the accented dictionary form ("hà", "nội").
+    pub fn suggest(&self, text: &str) -> Vec<(Token, Vec<String>)> {
+        self.tokenizer
+            .tokenize_with_tags(text, true)
+            .into_iter()
+            .filter(|token| token.token_type == TokenType::Word && !self.is_known(&token.text))
+            .map(|token| {
+                let suggestions = self.suggestions_for(&token.text);
+                (token, suggestions)
+            })
+            .collect()
+    }
+
+    fn suggestions_for(&self, word: &str) -> Vec<String> {
+        let canonical = canonicalize_for_matching(&word.to_lowercase());
+        let alphabet: Vec<char> = SPELLCHECK_ALPHABET.chars().collect();
+
+        let mut candidates = self.known(edits1(&canonical, &alphabet).into_iter());
+        if candidates.is_empty() {
+            let edits2 = edits1(&canonical, &alphabet)
+                .into_iter()
+                .flat_map(|edit| edits1(&edit, &alphabet));
+            candidates = self.known(edits2);
+        }
+
+        candidates.sort_by(|a, b| {
+            self.dictionary[b].cmp(&self.dictionary[a]).then_with(|| a.cmp(b))
+        });
+        candidates
+            .into_iter()
+            .map(|candidate| capitalize_like(word, &candidate))
+            .collect()
+    }
+
+    /// Filter candidate strings down to the ones present in the dictionary,
+    /// deduplicating (the same canonical form is often reachable by more
+    /// than one edit).
+    fn known(&self, candidates: impl Iterator<Item = String>) -> Vec<String> {
+        let mut seen = HashSet::new();
+        candidates
+            .filter(|candidate| self.dictionary.contains_key(candidate) && seen.insert(candidate.clone()))
+            .collect()
+    }
+}
+
+/// Restore `original`'s capitalization of its first letter onto `candidate`,
+/// so correcting "Ha" suggests "Hà" rather than "hà".
+fn capitalize_like(original: &str, candidate: &str) -> String {
+    let is_capitalized = original.chars().next().map(|c| c.is_uppercase()).unwrap_or(false);
+    if !is_capitalized {
+        return candidate.to_string();
+    }
+    let mut chars = candidate.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+        None => candidate.to_string(),
+    }
+}
+
+/// Every string reachable from `word` by deleting up to `max_distance`
+/// characters (including `word` itself, at distance 0) — the symmetric-delete
+/// index's candidate-generation step, expanded breadth-first one deletion at
+/// a time so duplicate variants across branches are only counted once.
+fn delete_variants(word: &str, max_distance: usize) -> HashSet<String> {
+    let mut variants = HashSet::new();
+    variants.insert(word.to_string());
+    let mut frontier = vec![word.to_string()];
+
+    for _ in 0..max_distance {
+        let mut next_frontier = Vec::new();
+        for candidate in &frontier {
+            let chars: Vec<char> = candidate.chars().collect();
+            for i in 0..chars.len() {
+                let mut deleted = chars.clone();
+                deleted.remove(i);
+                let deleted: String = deleted.into_iter().collect();
+                if variants.insert(deleted.clone()) {
+                    next_frontier.push(deleted);
+                }
+            }
+        }
+        frontier = next_frontier;
+    }
+
+    variants
+}
+
+/// True Levenshtein (single-character insert/delete/substitute) distance,
+/// used to rank symmetric-delete candidates precisely once the index has
+/// narrowed the field down to a small candidate set.
+fn levenshtein_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut dp = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in dp.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for j in 0..=b.len() {
+        dp[0][j] = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            dp[i][j] = (dp[i - 1][j] + 1)
+                .min(dp[i][j - 1] + 1)
+                .min(dp[i - 1][j - 1] + cost);
+        }
+    }
+
+    dp[a.len()][b.len()]
+}
+
+/// Depth-first collection of every word stored at or below `node`, for
+/// `suggest_completions`.
+fn collect_words(node: &PrefixTrieNode, out: &mut Vec<String>) {
+    if let Some(word) = &node.word {
+        out.push(word.clone());
+    }
+    for child in node.children.values() {
+        collect_words(child, out);
+    }
+}
+
+/// All strings within a single Norvig edit (insertion, deletion,
+/// substitution, transposition) of `word`, restricted to `alphabet`.
+fn edits1(word: &str, alphabet: &[char]) -> HashSet<String> {
+    let chars: Vec<char> = word.chars().collect();
+    let n = chars.len();
+    let mut edits = HashSet::new();
+
+    for i in 0..n {
+        let mut deleted = chars.clone();
+        deleted.remove(i);
+        edits.insert(deleted.into_iter().collect());
+    }
+
+    for i in 0..n.saturating_sub(1) {
+        let mut transposed = chars.clone();
+        transposed.swap(i, i + 1);
+        edits.insert(transposed.into_iter().collect());
+    }
+
+    for i in 0..n {
+        for &c in alphabet {
+            if chars[i] != c {
+                let mut replaced = chars.clone();
+                replaced[i] = c;
+                edits.insert(replaced.into_iter().collect());
+            }
+        }
+    }
+
+    for i in 0..=n {
+        for &c in alphabet {
+            let mut inserted = chars.clone();
+            inserted.insert(i, c);
+            edits.insert(inserted.into_iter().collect());
+        }
+    }
+
+    edits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_checker() -> SpellChecker {
+        let mut checker = SpellChecker::new();
+        checker.insert("hà", 1000);
+        checker.insert("nội", 900);
+        checker.insert("việt", 800);
+        checker.insert("nam", 700);
+        checker
+    }
+
+    #[test]
+    fn test_known_word_is_not_flagged() {
+        let checker = test_checker();
+        let flagged = checker.suggest("Việt Nam");
+        assert!(flagged.is_empty(), "Dictionary words should not be flagged: {:?}", flagged);
+    }
+
+    #[test]
+    fn test_diacritic_less_input_is_suggested_back_to_accented_form() {
+        let checker = test_checker();
+        let flagged = checker.suggest("Ha Noi");
+
+        assert_eq!(flagged.len(), 2);
+        assert_eq!(flagged[0].0.text, "Ha");
+        assert_eq!(flagged[0].1.first(), Some(&"Hà".to_string()));
+        assert_eq!(flagged[1].0.text, "Noi");
+        assert_eq!(flagged[1].1.first(), Some(&"Nội".to_string()));
+    }
+
+    #[test]
+    fn test_suggestions_ranked_by_frequency() {
+        let mut checker = test_checker();
+        // "ha" is one substitution away from both "hà" (freq 1000) and a
+        // lower-frequency rival entry; the higher-frequency word should rank first.
+        checker.insert("hb", 10);
+        let suggestions = checker.suggestions_for("ha");
+        let pos_ha = suggestions.iter().position(|s| s == "hà");
+        let pos_hb = suggestions.iter().position(|s| s == "hb");
+        if let (Some(pos_ha), Some(pos_hb)) = (pos_ha, pos_hb) {
+            assert!(pos_ha < pos_hb, "Higher-frequency candidate should rank first");
+        }
+    }
+
+    #[test]
+    fn test_is_known_is_case_insensitive() {
+        let checker = test_checker();
+        assert!(checker.is_known("HÀ"));
+        assert!(checker.is_known("hà"));
+    }
+
+    #[test]
+    fn test_correct_finds_known_word_is_empty() {
+        let checker = test_checker();
+        assert!(checker.correct("hà").is_empty());
+    }
+
+    #[test]
+    fn test_correct_via_symmetric_delete_index() {
+        let checker = test_checker();
+        // "Ha" is a single substitution (missing diacritic) from "hà",
+        // reachable through the symmetric-delete index since both share the
+        // one-deletion variant "h".
+        let corrections = checker.correct("Ha");
+        assert_eq!(corrections.first(), Some(&"Hà".to_string()));
+    }
+
+    #[test]
+    fn test_suggest_completions_ranks_by_frequency() {
+        let checker = test_checker();
+        let completions = checker.suggest_completions("n");
+        assert_eq!(completions, vec!["nội".to_string(), "nam".to_string()]);
+    }
+
+    #[test]
+    fn test_suggest_completions_unknown_prefix_is_empty() {
+        let checker = test_checker();
+        assert!(checker.suggest_completions("xyz").is_empty());
+    }
+}
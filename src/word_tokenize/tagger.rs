@@ -0,0 +1,178 @@
+use std::path::Path;
+
+use crate::word_tokenize::word_tokenize::{CrfTagger, DynamicLabels, SequenceTagger, VietnameseTokenizer};
+
+/// Default Vietnamese POS tagset (following the VLSP/VnCoreNLP convention):
+/// noun, proper noun, verb, adjective, pronoun, numeral, determiner, adverb,
+/// preposition, conjunction, particle and punctuation.
+fn default_pos_labels() -> Vec<String> {
+    ["N", "Np", "V", "A", "P", "M", "L", "R", "E", "C", "T", "CH"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+/// Default BIO phrase-chunk tagset: noun, verb, adjective and prepositional
+/// phrases, plus `O` for tokens outside any chunk.
+fn default_chunk_labels() -> Vec<String> {
+    ["B-NP", "I-NP", "B-VP", "I-VP", "B-AP", "I-AP", "B-PP", "I-PP", "O"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+/// Default BIO named-entity tagset: person, organization and location
+/// spans, plus `O` for tokens outside any entity.
+fn default_ner_labels() -> Vec<String> {
+    ["B-PER", "I-PER", "B-ORG", "I-ORG", "B-LOC", "I-LOC", "O"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+/// Feature templates shared by the POS and chunk models: besides the plain
+/// word-identity/context features `CRFModel` has always computed, a tagger
+/// over an open-ended label set also needs shape and affix features to
+/// generalize to words it never saw in training.
+fn default_pos_chunk_templates() -> Vec<String> {
+    [
+        "token", "prev_token", "next_token", "prefix1", "prefix2", "prefix3",
+        "suffix1", "suffix2", "suffix3", "shape", "contains_digit", "contains_hyphen",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect()
+}
+
+/// Tokenize-then-tag pipeline on top of the `CrfTagger`/`SequenceTagger`
+/// machinery `VietnameseWordSegmenter` uses for word boundaries (there
+/// instantiated with the word-boundary label set; here with a separate
+/// `DynamicLabels` instance per task), generalized to POS tagging and BIO
+/// phrase chunking. Each task is a separate model instance (each with its
+/// own label set and weights), left untrained (all-zero weights) until
+/// `load_pos_model`/`load_chunk_model` populates them from a real model file.
+pub struct VietnameseTagger {
+    tokenizer: VietnameseTokenizer,
+    pos_model: CrfTagger<DynamicLabels>,
+    chunk_model: CrfTagger<DynamicLabels>,
+    ner_model: CrfTagger<DynamicLabels>,
+}
+
+impl VietnameseTagger {
+    pub fn new() -> Self {
+        Self {
+            tokenizer: VietnameseTokenizer::new(),
+            pos_model: CrfTagger::with_labels(default_pos_labels(), default_pos_chunk_templates()),
+            chunk_model: CrfTagger::with_labels(default_chunk_labels(), default_pos_chunk_templates()),
+            ner_model: CrfTagger::with_labels(default_ner_labels(), default_pos_chunk_templates()),
+        }
+    }
+
+    /// Load trained POS-tagging weights (see `CrfTagger::load` for the file
+    /// format), replacing the default untrained label set with the one the
+    /// file declares.
+    pub fn load_pos_model(&mut self, model_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        self.pos_model.load(model_path)
+    }
+
+    /// Load trained BIO chunk-tagging weights, analogous to `load_pos_model`.
+    pub fn load_chunk_model(&mut self, model_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        self.chunk_model.load(model_path)
+    }
+
+    /// Load trained BIO named-entity-tagging weights, analogous to
+    /// `load_pos_model`. Since `CrfTagger`'s label set and feature extraction
+    /// are already parameterized rather than hard-wired to word boundaries,
+    /// this reuses exactly the same engine `pos_model`/`chunk_model` do, just
+    /// pointed at a model file trained over `default_ner_labels` (or whatever
+    /// label set the file itself declares).
+    pub fn load_ner_model(&mut self, model_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        self.ner_model.load(model_path)
+    }
+
+    fn tokenize_to_features(&self, sentence: &str) -> (Vec<String>, Vec<Vec<String>>) {
+        let tokens = self.tokenizer.tokenize(sentence);
+        let features: Vec<Vec<String>> = tokens.iter().map(|token| vec![token.clone()]).collect();
+        (tokens, features)
+    }
+
+    /// Tag each token of `sentence` with its part of speech.
+    pub fn pos_tag(&self, sentence: &str) -> Vec<(String, String)> {
+        let (tokens, features) = self.tokenize_to_features(sentence);
+        let tags = self.pos_model.predict(&features);
+        tokens.into_iter().zip(tags).collect()
+    }
+
+    /// Tag each token of `sentence` with both its part of speech and its BIO
+    /// phrase-chunk label, mirroring the `(word, pos, chunk)` triples CoNLL
+    /// chunking corpora ship.
+    pub fn chunk(&self, sentence: &str) -> Vec<(String, String, String)> {
+        let (tokens, features) = self.tokenize_to_features(sentence);
+        let pos_tags = self.pos_model.predict(&features);
+        let chunk_tags = self.chunk_model.predict(&features);
+
+        tokens
+            .into_iter()
+            .zip(pos_tags)
+            .zip(chunk_tags)
+            .map(|((token, pos), chunk)| (token, pos, chunk))
+            .collect()
+    }
+
+    /// Tag each token of `sentence` with its BIO named-entity label and a
+    /// per-token confidence (see `CRFModel::predict_with_scores`), so callers
+    /// can threshold which entity spans to trust before acting on them.
+    pub fn ner_tag(&self, sentence: &str, beam_width: usize) -> Vec<(String, String, f64)> {
+        let (tokens, _) = self.tokenize_to_features(sentence);
+        let tagged = self.ner_model.predict_with_scores(&tokens, beam_width);
+
+        tokens
+            .into_iter()
+            .zip(tagged)
+            .map(|(token, (label, confidence))| (token, label, confidence))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pos_tag_returns_one_tag_per_token() {
+        let tagger = VietnameseTagger::new();
+        let tagged = tagger.pos_tag("Bác sĩ bây giờ có thể");
+
+        assert_eq!(tagged.len(), 6);
+        for (token, tag) in &tagged {
+            assert!(!token.is_empty());
+            assert!(default_pos_labels().contains(tag));
+        }
+    }
+
+    #[test]
+    fn test_chunk_returns_word_pos_chunk_triples() {
+        let tagger = VietnameseTagger::new();
+        let chunked = tagger.chunk("Bác sĩ bây giờ");
+
+        assert_eq!(chunked.len(), 4);
+        for (token, pos, chunk) in &chunked {
+            assert!(!token.is_empty());
+            assert!(default_pos_labels().contains(pos));
+            assert!(default_chunk_labels().contains(chunk));
+        }
+    }
+
+    #[test]
+    fn test_ner_tag_returns_token_label_confidence_triples() {
+        let tagger = VietnameseTagger::new();
+        let tagged = tagger.ner_tag("Bác sĩ bây giờ", 4);
+
+        assert_eq!(tagged.len(), 4);
+        for (token, label, confidence) in &tagged {
+            assert!(!token.is_empty());
+            assert!(default_ner_labels().contains(label));
+            assert!((0.0..=1.0).contains(confidence));
+        }
+    }
+}
@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
+
+/// Tone value of a vowel with no tone mark (ngang). The other five tones are
+/// numbered 1 (huyền) through 5 (nặng), one more than their index into each
+/// vowel's 5-char tone group (huyền, sắc, hỏi, ngã, nặng) in `VOWEL_GROUPS`.
+const TONE_NGANG: u8 = 0;
+
+/// `(base_vowel, "huyền sắc hỏi ngã nặng")` for every Vietnamese vowel that
+/// takes a tone mark, lowercase only — uppercase is derived at table-build
+/// time via `char::to_uppercase`.
+const VOWEL_GROUPS: &[(char, &str)] = &[
+    ('a', "àáảãạ"),
+    ('ă', "ằắẳẵặ"),
+    ('â', "ầấẩẫậ"),
+    ('e', "èéẻẽẹ"),
+    ('ê', "ềếểễệ"),
+    ('i', "ìíỉĩị"),
+    ('o', "òóỏõọ"),
+    ('ô', "ồốổỗộ"),
+    ('ơ', "ờớởỡợ"),
+    ('u', "ùúủũụ"),
+    ('ư', "ừứửữự"),
+    ('y', "ỳýỷỹỵ"),
+];
+
+struct ToneTables {
+    /// Any vowel char (toned or bare) -> (lowercase base vowel, tone, is_upper).
+    vowel_info: HashMap<char, (char, u8, bool)>,
+    /// (lowercase base vowel, tone) -> lowercase toned char; apply uppercasing
+    /// separately so the table stays half the size.
+    compose: HashMap<(char, u8), char>,
+}
+
+static TONE_TABLES: Lazy<ToneTables> = Lazy::new(build_tone_tables);
+
+fn build_tone_tables() -> ToneTables {
+    let mut vowel_info = HashMap::new();
+    let mut compose = HashMap::new();
+
+    for &(base, toned_group) in VOWEL_GROUPS {
+        vowel_info.insert(base, (base, TONE_NGANG, false));
+        if let Some(upper_base) = base.to_uppercase().next() {
+            vowel_info.insert(upper_base, (base, TONE_NGANG, true));
+        }
+        compose.insert((base, TONE_NGANG), base);
+
+        for (i, toned) in toned_group.chars().enumerate() {
+            let tone = (i + 1) as u8;
+            vowel_info.insert(toned, (base, tone, false));
+            compose.insert((base, tone), toned);
+
+            if let Some(upper_toned) = toned.to_uppercase().next() {
+                vowel_info.insert(upper_toned, (base, tone, true));
+            }
+        }
+    }
+
+    ToneTables { vowel_info, compose }
+}
+
+/// Whether `old_style` Vietnamese tone placement is requested (e.g. "hoà") or
+/// the default `new_style` ("hòa"); see `reposition_tone_in_syllable`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToneStyle {
+    NewStyle,
+    OldStyle,
+}
+
+/// How much normalization `VietnameseTokenizer`/`VietnameseWordSegmenter`
+/// apply to input text before matching against it, mirroring the
+/// decompose/recompose knob SentencePiece-style tokenizers expose, but with
+/// an extra step for Vietnamese's tone-mark placement rules:
+///
+/// - `None`: use the text exactly as given.
+/// - `Nfc`: compose combining-diacritic sequences into precomposed
+///   characters, but leave each syllable's tone mark wherever it already
+///   sits (so "old style" and "new style" spellings stay distinct).
+/// - `NfcWithTonePlacement`: `Nfc`, plus reposition each syllable's tone mark
+///   onto the vowel standard orthography says should carry it, per `style`.
+///   This is the default, since it's what makes e.g. "hòa" and "hoà" compare
+///   equal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NormalizationMode {
+    None,
+    Nfc,
+    NfcWithTonePlacement,
+}
+
+impl Default for NormalizationMode {
+    fn default() -> Self {
+        NormalizationMode::NfcWithTonePlacement
+    }
+}
+
+/// Normalize `text` according to `mode`, using `style` for the tone-mark
+/// placement step when `mode` is `NfcWithTonePlacement`.
+pub fn normalize(text: &str, mode: NormalizationMode, style: ToneStyle) -> String {
+    match mode {
+        NormalizationMode::None => text.to_string(),
+        NormalizationMode::Nfc => text.nfc().collect(),
+        NormalizationMode::NfcWithTonePlacement => normalize_characters_in_text(text, style),
+    }
+}
+
+fn vowel_info(c: char) -> Option<(char, u8, bool)> {
+    TONE_TABLES.vowel_info.get(&c).copied()
+}
+
+fn compose_vowel(base: char, tone: u8, is_upper: bool) -> char {
+    let lower = *TONE_TABLES.compose.get(&(base, tone)).unwrap_or(&base);
+    if is_upper {
+        lower.to_uppercase().next().unwrap_or(lower)
+    } else {
+        lower
+    }
+}
+
+/// `compose_vowel`, exposed for callers outside this module that need the
+/// same `(base vowel, tone) -> precomposed char` table — e.g.
+/// `text_normalize::transcode`'s VNI/VIQR decoders, which resolve a legacy
+/// digit/punctuation tone marker down to one of `VOWEL_GROUPS`'s `(base,
+/// tone)` pairs and need the same composition this module's own NFC +
+/// tone-repositioning pipeline uses.
+pub fn compose_tone(base: char, tone: u8, is_upper: bool) -> char {
+    compose_vowel(base, tone, is_upper)
+}
+
+/// Apply Unicode NFC so combining diacritics become precomposed codepoints,
+/// then reposition each syllable's tone mark onto the vowel the standard
+/// Vietnamese orthography rules say should carry it.
+pub fn normalize_characters_in_text(text: &str, style: ToneStyle) -> String {
+    let nfc_text: String = text.nfc().collect();
+    let mut output = String::with_capacity(nfc_text.len());
+    let mut syllable: Vec<char> = Vec::new();
+
+    for c in nfc_text.chars() {
+        if c.is_alphabetic() {
+            syllable.push(c);
+        } else {
+            flush_syllable(&mut syllable, &mut output, style);
+            output.push(c);
+        }
+    }
+    flush_syllable(&mut syllable, &mut output, style);
+
+    output
+}
+
+fn flush_syllable(syllable: &mut Vec<char>, output: &mut String, style: ToneStyle) {
+    if syllable.is_empty() {
+        return;
+    }
+    reposition_tone_in_syllable(syllable, style);
+    output.extend(syllable.iter());
+    syllable.clear();
+}
+
+/// Move a syllable's tone mark onto the vowel that carries it by convention:
+/// - if the vowel cluster contains ê or ơ, the tone goes there;
+/// - otherwise, for a single vowel the tone stays on it;
+/// - for a cluster with no ê/ơ (new style only), the tone goes on the last
+///   vowel if the syllable has a final consonant (e.g. "toán"), or the first
+///   vowel if it ends in the vowel cluster itself (e.g. "hòa");
+/// - old style skips all of the above and always places the tone on the last
+///   vowel of the cluster (e.g. "hoà", "thuý");
+/// - a leading "qu"/"gi" glide vowel is skipped when choosing the target, so
+///   the tone shifts onto the main vowel that follows it.
+fn reposition_tone_in_syllable(syllable: &mut [char], style: ToneStyle) {
+    let vowels: Vec<(usize, char, u8, bool)> = syllable
+        .iter()
+        .enumerate()
+        .filter_map(|(i, &c)| vowel_info(c).map(|(base, tone, is_upper)| (i, base, tone, is_upper)))
+        .collect();
+
+    if vowels.is_empty() {
+        return;
+    }
+
+    let current_tone = vowels.iter().find(|&&(_, _, tone, _)| tone != TONE_NGANG).map(|&(_, _, tone, _)| tone);
+    let Some(current_tone) = current_tone else {
+        return;
+    };
+
+    // Strip the tone off wherever it currently sits, keeping the base vowel.
+    for &(i, base, tone, is_upper) in &vowels {
+        if tone != TONE_NGANG {
+            syllable[i] = compose_vowel(base, TONE_NGANG, is_upper);
+        }
+    }
+
+    // Skip a leading "qu"/"gi" glide vowel: it is not the tone-bearing nucleus.
+    let mut candidates = vowels.clone();
+    if candidates.len() > 1 && candidates[0].0 <= 1 {
+        let prefix: String = syllable.iter().take(2).collect::<String>().to_lowercase();
+        if prefix == "qu" || prefix == "gi" {
+            candidates.remove(0);
+        }
+    }
+    if candidates.is_empty() {
+        candidates = vowels;
+    }
+
+    let has_final_consonant = candidates
+        .last()
+        .map(|&(i, _, _, _)| i + 1 < syllable.len())
+        .unwrap_or(false);
+
+    let &(target, target_base, _, target_is_upper) = if style == ToneStyle::OldStyle {
+        candidates.last().unwrap()
+    } else if let Some(main_vowel) = candidates.iter().find(|&&(_, base, _, _)| base == 'ê' || base == 'ơ') {
+        main_vowel
+    } else if candidates.len() == 1 || !has_final_consonant {
+        candidates.first().unwrap()
+    } else {
+        candidates.last().unwrap()
+    };
+
+    syllable[target] = compose_vowel(target_base, current_tone, target_is_upper);
+}
+
+/// Collapse Latin-lookalike Cyrillic codepoints (the dominant source of
+/// visually-identical homoglyph spoofing) to their canonical Vietnamese form.
+const LOOKALIKE_PAIRS: &[(char, char)] = &[
+    ('а', 'a'), ('е', 'e'), ('о', 'o'), ('р', 'p'), ('с', 'c'),
+    ('у', 'y'), ('х', 'x'), ('і', 'i'),
+    ('А', 'A'), ('В', 'B'), ('Е', 'E'), ('К', 'K'), ('М', 'M'),
+    ('Н', 'H'), ('О', 'O'), ('Р', 'P'), ('С', 'C'), ('Т', 'T'),
+    ('Х', 'X'), ('У', 'Y'),
+];
+
+static LOOKALIKE_MAP: Lazy<HashMap<char, char>> =
+    Lazy::new(|| LOOKALIKE_PAIRS.iter().copied().collect());
+
+pub fn fold_lookalike_codepoints(text: &str) -> String {
+    text.chars()
+        .map(|c| LOOKALIKE_MAP.get(&c).copied().unwrap_or(c))
+        .collect()
+}
+
+/// Canonical form used to compare text for equivalence regardless of
+/// lookalike codepoints or tone-mark placement style: fold lookalikes, then
+/// NFC-normalize and reposition tones to the new-style convention. Folding
+/// and tone repositioning preserve the char count, and so does NFC on text
+/// that is already composed; NFC shortens text that holds a base letter
+/// followed by a combining mark, so char offsets found against the canonical
+/// form line up with the original only when the original is NFC.
+pub fn canonicalize_for_matching(text: &str) -> String {
+    normalize_characters_in_text(&fold_lookalike_codepoints(text), ToneStyle::NewStyle)
+}
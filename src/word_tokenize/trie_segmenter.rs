@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use crate::word_tokenize::word_tokenize::VietnameseTokenizer;
+
+/// A node in the maximum-matching segmentation trie, keyed syllable-by-syllable
+/// (mirroring `WordDictionary`'s trie) with an end-of-word marker instead of a
+/// frequency, since this segmenter has no notion of weighting between
+/// candidates — only whether a path is a known word at all.
+#[derive(Debug, Default)]
+struct TrieNode {
+    children: HashMap<String, TrieNode>,
+    is_word: bool,
+}
+
+/// Deterministic, explainable alternative to the CRF-driven
+/// `VietnameseWordSegmenter`: segments by greedily taking the longest
+/// dictionary word starting at each syllable position (longest-maximal
+/// matching over a prefix trie), falling back to a single syllable wherever
+/// no trie path matches. Needs no trained model, so it's also a reasonable
+/// fallback to construct when `FastCRFSequenceTagger::load` can't find one.
+pub struct TrieSegmenter {
+    tokenizer: VietnameseTokenizer,
+    root: TrieNode,
+    max_word_syllables: usize,
+}
+
+impl TrieSegmenter {
+    pub fn new() -> Self {
+        Self {
+            tokenizer: VietnameseTokenizer::new(),
+            root: TrieNode::default(),
+            max_word_syllables: 1,
+        }
+    }
+
+    /// Build a segmenter whose dictionary is exactly `words`, each a
+    /// space-joined sequence of syllables (e.g. `"nghiên cứu"`).
+    pub fn with_dictionary(words: &[String]) -> Self {
+        let mut segmenter = Self::new();
+        for word in words {
+            segmenter.insert(word);
+        }
+        segmenter
+    }
+
+    /// Add a single word to the dictionary trie.
+    pub fn insert(&mut self, word: &str) {
+        let syllables: Vec<&str> = word.split_whitespace().collect();
+        if syllables.is_empty() {
+            return;
+        }
+
+        let mut node = &mut self.root;
+        for syllable in &syllables {
+            node = node
+                .children
+                .entry(syllable.to_string())
+                .or_insert_with(TrieNode::default);
+        }
+        node.is_word = true;
+        self.max_word_syllables = self.max_word_syllables.max(syllables.len());
+    }
+
+    /// Greedily consume the longest trie-known word starting at each
+    /// position, falling back to a single syllable when no trie path from
+    /// that position ends on a known word.
+    fn segment_syllables(&self, syllables: &[String]) -> Vec<String> {
+        let n = syllables.len();
+        let mut words = Vec::new();
+        let mut i = 0;
+
+        while i < n {
+            let max_j = (i + self.max_word_syllables).min(n);
+            let mut best_j = None;
+            let mut node = &self.root;
+
+            for j in (i + 1)..=max_j {
+                match node.children.get(syllables[j - 1].as_str()) {
+                    Some(child) => {
+                        node = child;
+                        if node.is_word {
+                            best_j = Some(j);
+                        }
+                    }
+                    None => break,
+                }
+            }
+
+            let j = best_j.unwrap_or(i + 1);
+            words.push(syllables[i..j].join(" "));
+            i = j;
+        }
+
+        words
+    }
+
+    /// Tokenize into syllables, merge them into words via longest dictionary
+    /// matching, and format the result exactly like
+    /// `VietnameseWordSegmenter::word_tokenize_as_text` does (multi-syllable
+    /// words underscore-joined, words space-separated), so callers can swap
+    /// between the two backends transparently.
+    pub fn word_tokenize(&self, sentence: &str) -> String {
+        let syllables = self.tokenizer.tokenize(sentence);
+        self.segment_syllables(&syllables)
+            .iter()
+            .map(|word| word.replace(' ', "_"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trie_segmenter_merges_known_compound() {
+        let segmenter = TrieSegmenter::with_dictionary(&[
+            "nghiên cứu".to_string(),
+            "khoa học".to_string(),
+        ]);
+        let text = segmenter.word_tokenize("nghiên cứu khoa học");
+        assert_eq!(text, "nghiên_cứu khoa_học");
+    }
+
+    #[test]
+    fn test_trie_segmenter_falls_back_to_single_syllables_when_unknown() {
+        let segmenter = TrieSegmenter::new();
+        let text = segmenter.word_tokenize("bây giờ");
+        assert_eq!(text, "bây giờ");
+    }
+
+    #[test]
+    fn test_trie_segmenter_prefers_longest_match() {
+        let segmenter = TrieSegmenter::with_dictionary(&[
+            "Viện nghiên cứu".to_string(),
+            "Viện nghiên cứu khoa học".to_string(),
+        ]);
+        let text = segmenter.word_tokenize("Viện nghiên cứu khoa học hàng đầu");
+        assert!(text.starts_with("Viện_nghiên_cứu_khoa_học"));
+    }
+
+    #[test]
+    fn test_trie_segmenter_word_tokenize_uses_underscores() {
+        let segmenter = TrieSegmenter::with_dictionary(&["bác sĩ".to_string()]);
+        let text = segmenter.word_tokenize("bác sĩ bây giờ");
+        assert_eq!(text, "bác_sĩ bây giờ");
+    }
+}
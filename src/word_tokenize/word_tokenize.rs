@@ -1,8 +1,19 @@
+use aho_corasick::{AhoCorasick, MatchKind};
 use regex::Regex;
-use std::collections::HashMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap};
+use std::fs;
+use std::iter::once;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use once_cell::sync::Lazy;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::added_vocabulary::{AddedToken, AddedVocabulary};
+use crate::word_tokenize::dictionary::{segment_tokens_with_dictionary, segment_with_dictionary, WordDictionary};
+use crate::word_tokenize::spellcheck::SpellChecker;
+use crate::word_tokenize::tone_normalize::{self, NormalizationMode, ToneStyle};
 
 // Vietnamese character constants
 const VIETNAMESE_CHARACTERS_UPPER: &str = "ABCDEFGHIJKLMNOPQRSTUVXYZ\
@@ -48,11 +59,19 @@ fn lower_class() -> String {
     upper_class().to_lowercase()
 }
 
-fn word_class() -> String {
-    format!("[{}{}]", 
-        "A-ZÀÁẢÃẠĂẰẮẲẴẶÂẦẤẨẪẬĐÈÉẺẼẸÊỀẾỂỄỆÌÍỈĨỊÒÓỎÕỌÔỒỐỔỖỘƠỜỚỞỠỢÙÚỦŨỤƯỪỨỬỮỰỲÝỶỸỴ",
-        "a-zàáảãạăằắẳẵặâầấẩẫậđèéẻẽẹêềếểễệìíỉĩịòóỏõọôồốổỗộơờớởỡợùúủũụưừứửữựỳýỷỹỵ"
-    )
+/// Word-character class used throughout `build_regex_patterns`. When
+/// `ignore_case` is set the combined pattern is compiled with the `(?i)`
+/// flag, so listing both cases here would just be redundant — a single case
+/// is enough and keeps the class (and the compiled automaton) smaller.
+fn word_class(ignore_case: bool) -> String {
+    if ignore_case {
+        upper_class()
+    } else {
+        format!("[{}{}]",
+            "A-ZÀÁẢÃẠĂẰẮẲẴẶÂẦẤẨẪẬĐÈÉẺẼẸÊỀẾỂỄỆÌÍỈĨỊÒÓỎÕỌÔỒỐỔỖỘƠỜỚỞỠỢÙÚỦŨỤƯỪỨỬỮỰỲÝỶỸỴ",
+            "a-zàáảãạăằắẳẵặâầấẩẫậđèéẻẽẹêềếểễệìíỉĩịòóỏõọôồốổỗộơờớởỡợùúủũụưừứửữựỳýỷỹỵ"
+        )
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -72,47 +91,243 @@ pub enum TokenType {
     Symbol,
     NonWord,
     FixedWords,
+    AddedToken,
 }
 
 #[derive(Debug, Clone)]
 pub struct Token {
     pub text: String,
     pub token_type: TokenType,
+    /// Character index of the token's first character in the (normalized) source text.
+    pub start: usize,
+    /// Character index just past the token's last character in the (normalized) source text.
+    pub end: usize,
+}
+
+/// Match-mode flags for `VietnameseTokenizer::with_options`, threaded into
+/// `build_regex_patterns` and the fixed-word matchers: `ignore_case` compiles
+/// the combined pattern with the `(?i)` flag so abbreviations and words match
+/// regardless of case; `fold_diacritics` additionally matches fixed words
+/// regardless of Vietnamese diacritics, by comparing the `fold`ed form of
+/// both the pattern and the input text instead of the raw form. The regex
+/// patterns, abbreviations included, always run on the unfolded text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenizerOptions {
+    pub ignore_case: bool,
+    pub fold_diacritics: bool,
 }
 
 pub struct VietnameseTokenizer {
     patterns: Regex,
-    use_character_normalize: bool,
+    fixed_words: Vec<AddedToken>,
+    /// Aho-Corasick automaton over the `normalized: true` subset of
+    /// `fixed_words`, matched against each segment's normalized text so e.g.
+    /// both "hoà" and "hòa" hit the same registered entry. Built once so
+    /// fixed-phrase matching against a large dictionary (named entities,
+    /// institution names, ...) stays near-linear instead of the
+    /// `O(vocab·text)` a per-position vocabulary scan would cost. `None` when
+    /// there is no such subset.
+    normalized_fixed_matcher: Option<AhoCorasick>,
+    /// Parallel to `normalized_fixed_matcher`: the subset of `fixed_words` it
+    /// was built from, in the same pattern order, so a match's
+    /// `Match::pattern()` index looks its originating `AddedToken` back up.
+    normalized_fixed_words: Vec<AddedToken>,
+    /// Aho-Corasick automaton over the `normalized: false` subset of
+    /// `fixed_words`, matched against each segment's *raw* text before
+    /// normalization runs, so e.g. a product code or hashtag registered this
+    /// way survives untouched.
+    raw_fixed_matcher: Option<AhoCorasick>,
+    /// Parallel to `raw_fixed_matcher`, analogous to `normalized_fixed_words`.
+    raw_fixed_words: Vec<AddedToken>,
+    normalization_mode: NormalizationMode,
     use_token_normalize: bool,
+    /// New-style (default) vs old-style Vietnamese tone-mark placement; see
+    /// `tone_normalize::reposition_tone_in_syllable`. Only takes effect when
+    /// `normalization_mode` is `NfcWithTonePlacement`.
+    old_style_tone: bool,
+    options: TokenizerOptions,
+    added_vocabulary: AddedVocabulary,
+}
+
+/// Plain-data mirror of `VietnameseTokenizer` used to (de)serialize it, since the
+/// compiled `Regex` itself cannot be serialized and must be rebuilt on load.
+#[derive(Serialize, Deserialize)]
+struct VietnameseTokenizerState {
+    fixed_words: Vec<AddedToken>,
+    #[serde(default)]
+    normalization_mode: NormalizationMode,
+    use_token_normalize: bool,
+    #[serde(default)]
+    old_style_tone: bool,
+    #[serde(default)]
+    options: TokenizerOptions,
+    #[serde(default)]
+    added_vocabulary: AddedVocabulary,
+}
+
+impl Serialize for VietnameseTokenizer {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        VietnameseTokenizerState {
+            fixed_words: self.fixed_words.clone(),
+            normalization_mode: self.normalization_mode,
+            use_token_normalize: self.use_token_normalize,
+            old_style_tone: self.old_style_tone,
+            options: self.options,
+            added_vocabulary: self.added_vocabulary.clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for VietnameseTokenizer {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let state = VietnameseTokenizerState::deserialize(deserializer)?;
+        let (normalized_fixed_matcher, normalized_fixed_words) =
+            build_fixed_word_matcher_for(&state.fixed_words, true, state.options.fold_diacritics);
+        let (raw_fixed_matcher, raw_fixed_words) =
+            build_fixed_word_matcher_for(&state.fixed_words, false, state.options.fold_diacritics);
+        Ok(Self {
+            patterns: build_regex_patterns(&state.options),
+            normalized_fixed_matcher,
+            normalized_fixed_words,
+            raw_fixed_matcher,
+            raw_fixed_words,
+            fixed_words: state.fixed_words,
+            normalization_mode: state.normalization_mode,
+            use_token_normalize: state.use_token_normalize,
+            old_style_tone: state.old_style_tone,
+            options: state.options,
+            added_vocabulary: state.added_vocabulary,
+        })
+    }
 }
 
 impl VietnameseTokenizer {
     pub fn new() -> Self {
-        let patterns = build_regex_patterns(&[]);
+        Self::with_options(TokenizerOptions::default())
+    }
+
+    /// Build a tokenizer whose case-sensitivity and diacritic-matching
+    /// behavior is controlled by `options` (see `TokenizerOptions`), with no
+    /// fixed-word vocabulary — the same sugar relationship `with_fixed_words`
+    /// has to `with_fixed_tokens`.
+    pub fn with_options(options: TokenizerOptions) -> Self {
         Self {
-            patterns,
-            use_character_normalize: true,
+            patterns: build_regex_patterns(&options),
+            fixed_words: Vec::new(),
+            normalized_fixed_matcher: None,
+            normalized_fixed_words: Vec::new(),
+            raw_fixed_matcher: None,
+            raw_fixed_words: Vec::new(),
+            normalization_mode: NormalizationMode::default(),
             use_token_normalize: true,
+            old_style_tone: false,
+            options,
+            added_vocabulary: AddedVocabulary::new(),
         }
     }
 
+    /// Sugar over `with_fixed_tokens`: builds a default `AddedToken` per
+    /// word (`single_word: true`, `normalized: true`, no stripping), matching
+    /// the fixed-word matching behavior this type always had before
+    /// per-entry configuration existed.
     pub fn with_fixed_words(fixed_words: &[String]) -> Self {
-        let patterns = build_regex_patterns(fixed_words);
+        let fixed_tokens: Vec<AddedToken> = fixed_words
+            .iter()
+            .map(|word| AddedToken {
+                content: word.clone(),
+                single_word: true,
+                lstrip: false,
+                rstrip: false,
+                normalized: true,
+            })
+            .collect();
+        Self::with_fixed_tokens(&fixed_tokens)
+    }
+
+    /// Build a tokenizer whose fixed-phrase vocabulary is `fixed_tokens`,
+    /// with full per-entry control over matching behavior (see
+    /// `AddedToken`): `single_word` requires the match to fall on a token
+    /// boundary rather than mid-syllable, `lstrip`/`rstrip` absorb adjacent
+    /// whitespace into the emitted span, and `normalized` chooses whether
+    /// the entry is matched against the normalized text (so tone-mark and
+    /// lookalike-codepoint variants also match) or the raw input (so e.g. a
+    /// product code or hashtag survives untouched by normalization).
+    pub fn with_fixed_tokens(fixed_tokens: &[AddedToken]) -> Self {
+        let options = TokenizerOptions::default();
+        let (normalized_fixed_matcher, normalized_fixed_words) =
+            build_fixed_word_matcher_for(fixed_tokens, true, options.fold_diacritics);
+        let (raw_fixed_matcher, raw_fixed_words) =
+            build_fixed_word_matcher_for(fixed_tokens, false, options.fold_diacritics);
         Self {
-            patterns,
-            use_character_normalize: true,
+            patterns: build_regex_patterns(&options),
+            fixed_words: fixed_tokens.to_vec(),
+            normalized_fixed_matcher,
+            normalized_fixed_words,
+            raw_fixed_matcher,
+            raw_fixed_words,
+            normalization_mode: NormalizationMode::default(),
             use_token_normalize: true,
+            old_style_tone: false,
+            options,
+            added_vocabulary: AddedVocabulary::new(),
         }
     }
 
-    pub fn set_character_normalize(&mut self, enabled: bool) {
-        self.use_character_normalize = enabled;
+    /// Override how much normalization runs before matching (see
+    /// `NormalizationMode`); callers whose input is already normalized can
+    /// pass `NormalizationMode::None` to skip the pass entirely.
+    pub fn set_normalization_mode(&mut self, mode: NormalizationMode) {
+        self.normalization_mode = mode;
     }
 
     pub fn set_token_normalize(&mut self, enabled: bool) {
         self.use_token_normalize = enabled;
     }
 
+    /// Toggle old-style ("hoà") vs the default new-style ("hòa") Vietnamese
+    /// tone-mark placement used by character normalization.
+    pub fn set_old_style_tone_placement(&mut self, enabled: bool) {
+        self.old_style_tone = enabled;
+    }
+
+    /// Override the case-sensitivity/diacritic-matching flags (see
+    /// `TokenizerOptions`), rebuilding the regex patterns and fixed-word
+    /// matchers so the change takes effect immediately.
+    pub fn set_options(&mut self, options: TokenizerOptions) {
+        let (normalized_fixed_matcher, normalized_fixed_words) =
+            build_fixed_word_matcher_for(&self.fixed_words, true, options.fold_diacritics);
+        let (raw_fixed_matcher, raw_fixed_words) =
+            build_fixed_word_matcher_for(&self.fixed_words, false, options.fold_diacritics);
+        self.patterns = build_regex_patterns(&options);
+        self.normalized_fixed_matcher = normalized_fixed_matcher;
+        self.normalized_fixed_words = normalized_fixed_words;
+        self.raw_fixed_matcher = raw_fixed_matcher;
+        self.raw_fixed_words = raw_fixed_words;
+        self.options = options;
+    }
+
+    /// Register tokens (e.g. `<s>`, `</s>`, `<mask>`) that must never be split
+    /// by the regular tokenizer patterns and are never touched by
+    /// normalization.
+    pub fn add_special_tokens(&mut self, tokens: &[String]) {
+        self.added_vocabulary
+            .add_tokens(tokens.iter().map(|t| AddedToken::new(t.clone())));
+    }
+
+    /// Register added tokens with full control over matching/stripping
+    /// behavior (see `AddedToken`).
+    pub fn add_added_tokens(&mut self, tokens: Vec<AddedToken>) {
+        self.added_vocabulary.add_tokens(tokens);
+    }
+
+    /// Repoint an already-registered added token's surface content to `new`
+    /// without changing its id slot. Returns `false` if `old` was never
+    /// registered.
+    pub fn assign_token(&mut self, old: &str, new: &str) -> bool {
+        self.added_vocabulary.assign_token(old, new)
+    }
+
     pub fn tokenize(&self, text: &str) -> Vec<String> {
         self.tokenize_with_tags(text, false)
             .into_iter()
@@ -120,29 +335,171 @@ impl VietnameseTokenizer {
             .collect()
     }
 
-    pub fn tokenize_with_tags(&self, text: &str, _include_tags: bool) -> Vec<Token> {
-        let normalized_text = if self.use_character_normalize {
-            normalize_characters_in_text(text)
-        } else {
-            text.to_string()
-        };
+    pub fn tokenize_with_tags(&self, text: &str, include_tags: bool) -> Vec<Token> {
+        self.tokenize_with_offsets(text, include_tags).0
+    }
 
+    /// Like `tokenize_with_tags`, but also returns the exact string that
+    /// `Token::start`/`Token::end` are character offsets into: added-token
+    /// segments and raw fixed-word matches appear verbatim (they bypass
+    /// normalization), and the gaps between them appear in their normalized
+    /// form. Offset-reporting consumers (e.g. `SubwordEncoder`) need this
+    /// rather than the original `text`, since normalization is not always
+    /// character-count preserving — NFC composition can fold a decomposed
+    /// base+combining-mark sequence into a single precomposed character.
+    pub fn tokenize_with_offsets(&self, text: &str, _include_tags: bool) -> (Vec<Token>, String) {
         let mut tokens = Vec::new();
-        for cap in self.patterns.captures_iter(&normalized_text) {
-            if let Some((token_text, token_type)) = extract_match(&cap) {
+        let mut normalized_source = String::with_capacity(text.len());
+        let mut char_offset = 0usize;
+
+        // Added tokens are matched against the raw input before normalization
+        // or the regex patterns run, so they survive as single atomic tokens.
+        for (is_added, segment) in self.added_vocabulary.split(text) {
+            if is_added {
+                let len_chars = segment.chars().count();
+                normalized_source.push_str(&segment);
+                tokens.push(Token {
+                    text: segment,
+                    token_type: TokenType::AddedToken,
+                    start: char_offset,
+                    end: char_offset + len_chars,
+                });
+                char_offset += len_chars;
+                continue;
+            }
+
+            // Fixed words registered with `normalized: false` are matched
+            // against the raw segment before normalization runs, so they
+            // come out untouched (product codes, hashtags, ...); everything
+            // else — including the gaps between raw matches — goes through
+            // `tokenize_normalized_chunk`'s usual normalize/fixed-word/regex
+            // pipeline. `char_offset` tracks the characters actually emitted
+            // so far (raw where unnormalized, normalized where not), not the
+            // raw segment's own character count, so it stays in sync with
+            // `normalized_source` even when normalization changes length.
+            let raw_matches = self
+                .raw_fixed_matcher
+                .as_ref()
+                .map(|matcher| fixed_word_spans_for(matcher, &segment, &self.raw_fixed_words, self.options.fold_diacritics))
+                .unwrap_or_default();
+
+            let mut cursor = 0usize;
+            for (match_start, match_end) in raw_matches {
+                if match_start > cursor {
+                    let normalized_chunk =
+                        self.tokenize_normalized_chunk(&segment[cursor..match_start], char_offset, &mut tokens);
+                    char_offset += normalized_chunk.chars().count();
+                    normalized_source.push_str(&normalized_chunk);
+                }
+
+                let raw_match = &segment[match_start..match_end];
+                let len_chars = raw_match.chars().count();
+                tokens.push(Token {
+                    text: raw_match.to_string(),
+                    token_type: TokenType::FixedWords,
+                    start: char_offset,
+                    end: char_offset + len_chars,
+                });
+                normalized_source.push_str(raw_match);
+                char_offset += len_chars;
+
+                cursor = match_end;
+            }
+            if cursor < segment.len() {
+                let normalized_chunk = self.tokenize_normalized_chunk(&segment[cursor..], char_offset, &mut tokens);
+                char_offset += normalized_chunk.chars().count();
+                normalized_source.push_str(&normalized_chunk);
+            }
+        }
+
+        (tokens, normalized_source)
+    }
+
+    /// Normalize `raw_chunk` (a slice of a segment not claimed by a raw fixed
+    /// word), match the `normalized: true` fixed-word subset against it, and
+    /// hand the gaps between those matches to the regular tokenization
+    /// patterns — i.e. everything `tokenize_with_tags` used to do directly
+    /// over a whole segment, now reusable over any raw sub-slice of one.
+    /// Returns the normalized text it tokenized, so the caller can track how
+    /// many characters it actually contributed (not necessarily `raw_chunk`'s
+    /// own character count — see `tokenize_with_offsets`).
+    fn tokenize_normalized_chunk(&self, raw_chunk: &str, char_offset: usize, tokens: &mut Vec<Token>) -> String {
+        let normalized_text = normalize_characters_in_text(raw_chunk, self.normalization_mode, self.old_style_tone);
+
+        // Track how many characters precede each byte offset so matches can
+        // be reported as character spans instead of raw byte offsets.
+        let char_index_of_byte = byte_to_char_index_table(&normalized_text);
+
+        // The fixed-word automaton runs over the whole normalized text first;
+        // only the gaps between its (non-overlapping, longest-wins) matches
+        // are handed to the regular tokenization patterns.
+        let fixed_matches = self
+            .normalized_fixed_matcher
+            .as_ref()
+            .map(|matcher| fixed_word_spans_for(matcher, &normalized_text, &self.normalized_fixed_words, self.options.fold_diacritics))
+            .unwrap_or_default();
+
+        let mut cursor = 0usize;
+        for (match_start, match_end) in fixed_matches {
+            if match_start > cursor {
+                self.push_regex_tokens(
+                    &normalized_text[cursor..match_start],
+                    cursor,
+                    char_offset,
+                    &char_index_of_byte,
+                    tokens,
+                );
+            }
+
+            let token_text = normalized_text[match_start..match_end].to_string();
+            let final_text = if self.use_token_normalize {
+                token_normalize(&token_text, self.normalization_mode, self.old_style_tone)
+            } else {
+                token_text
+            };
+            tokens.push(Token {
+                text: final_text,
+                token_type: TokenType::FixedWords,
+                start: char_offset + char_index_of_byte[match_start],
+                end: char_offset + char_index_of_byte[match_end],
+            });
+
+            cursor = match_end;
+        }
+        if cursor < normalized_text.len() {
+            self.push_regex_tokens(&normalized_text[cursor..], cursor, char_offset, &char_index_of_byte, tokens);
+        }
+
+        normalized_text
+    }
+
+    /// Run the regular tokenization patterns over `text` (a slice of a
+    /// normalized segment starting `byte_offset` bytes into it) and append
+    /// the resulting tokens, translating byte spans back to absolute
+    /// character offsets via `char_index_of_byte` and `char_offset`.
+    fn push_regex_tokens(
+        &self,
+        text: &str,
+        byte_offset: usize,
+        char_offset: usize,
+        char_index_of_byte: &[usize],
+        tokens: &mut Vec<Token>,
+    ) {
+        for cap in self.patterns.captures_iter(text) {
+            if let Some((token_text, token_type, byte_start, byte_end)) = extract_match(&cap) {
                 let final_text = if self.use_token_normalize {
-                    token_normalize(&token_text, self.use_character_normalize)
+                    token_normalize(&token_text, self.normalization_mode, self.old_style_tone)
                 } else {
                     token_text
                 };
                 tokens.push(Token {
                     text: final_text,
                     token_type,
+                    start: char_offset + char_index_of_byte[byte_offset + byte_start],
+                    end: char_offset + char_index_of_byte[byte_offset + byte_end],
                 });
             }
         }
-
-        tokens
     }
 
     pub fn tokenize_as_text(&self, text: &str) -> String {
@@ -156,65 +513,208 @@ pub trait SequenceTagger {
     fn predict(&self, features: &[Vec<String>]) -> Vec<String>;
 }
 
+/// A tagger's label alphabet and feature-template list — the two things
+/// that change between word-boundary tagging, POS tagging, BIO chunking and
+/// NER, while the Viterbi/beam-search engine itself (`CrfTagger`) stays the
+/// same.
+pub trait LabelSet: Clone {
+    fn labels(&self) -> &[String];
+    fn feature_templates(&self) -> &[String];
+    /// Build a label set from a model file's `LABELS`/`TEMPLATES`
+    /// directives (see `CrfTagger::load`), overriding whatever default
+    /// label alphabet `Self` otherwise ships with.
+    fn from_loaded(labels: Vec<String>, feature_templates: Vec<String>) -> Self;
+}
+
+/// The two-label word-segmentation scheme (`B-W`/`I-W`) `VietnameseWordSegmenter`
+/// tags syllables with, with the hand-written feature templates this model
+/// shipped with before templates were configurable.
+#[derive(Debug, Clone)]
+pub struct WordBoundaryLabels {
+    labels: Vec<String>,
+    feature_templates: Vec<String>,
+}
+
+impl Default for WordBoundaryLabels {
+    fn default() -> Self {
+        Self {
+            labels: vec!["B-W".to_string(), "I-W".to_string()],
+            feature_templates: Vec::new(),
+        }
+    }
+}
+
+impl LabelSet for WordBoundaryLabels {
+    fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    fn feature_templates(&self) -> &[String] {
+        &self.feature_templates
+    }
+
+    fn from_loaded(labels: Vec<String>, feature_templates: Vec<String>) -> Self {
+        Self { labels, feature_templates }
+    }
+}
+
+/// A runtime-configured label alphabet for tasks beyond word-boundary
+/// tagging (POS tagging, BIO chunking, NER, ...), where the label set and
+/// feature templates are supplied by the caller rather than fixed at
+/// compile time — see `CrfTagger::with_labels`.
+#[derive(Debug, Clone, Default)]
+pub struct DynamicLabels {
+    labels: Vec<String>,
+    feature_templates: Vec<String>,
+}
+
+impl LabelSet for DynamicLabels {
+    fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    fn feature_templates(&self) -> &[String] {
+        &self.feature_templates
+    }
+
+    fn from_loaded(labels: Vec<String>, feature_templates: Vec<String>) -> Self {
+        Self { labels, feature_templates }
+    }
+}
+
+/// Linear-chain CRF tagger, generic over its label alphabet `L`: the
+/// Viterbi/beam-search engine and feature extraction are shared by every
+/// sequence-labeling task (word segmentation, POS tagging, BIO chunking,
+/// NER, ...), only the label set and feature templates differ between them.
 #[derive(Debug)]
-pub struct CRFModel {
+pub struct CrfTagger<L: LabelSet> {
     // This would contain the actual CRF model parameters
     // For now, we'll use a simplified representation
     weights: HashMap<String, f64>,
-    labels: Vec<String>,
-    feature_templates: Vec<String>,
+    label_set: L,
     loaded: bool,
 }
 
-impl CRFModel {
+/// Word-boundary tagger, i.e. the model `VietnameseWordSegmenter` runs —
+/// kept as a type alias rather than a fresh name so the many existing
+/// `CRFModel::new()` call sites (tests included) don't need to change.
+pub type CRFModel = CrfTagger<WordBoundaryLabels>;
+
+impl<L: LabelSet + Default> CrfTagger<L> {
     pub fn new() -> Self {
         Self {
             weights: HashMap::new(),
-            labels: vec!["B-W".to_string(), "I-W".to_string()],
-            feature_templates: Vec::new(),
+            label_set: L::default(),
             loaded: false,
         }
     }
+}
+
+impl CrfTagger<DynamicLabels> {
+    /// Build an untrained tagger over an arbitrary label set and feature
+    /// template list, for tasks beyond word-boundary tagging (POS tagging,
+    /// BIO chunking, ...). Unlike `new()`, the model is immediately usable:
+    /// `predict` runs real Viterbi decoding, just over all-zero weights
+    /// until `load` populates them from a trained model file.
+    pub fn with_labels(labels: Vec<String>, feature_templates: Vec<String>) -> Self {
+        Self {
+            weights: HashMap::new(),
+            label_set: DynamicLabels { labels, feature_templates },
+            loaded: true,
+        }
+    }
+}
+
+impl<L: LabelSet> CrfTagger<L> {
+    /// The feature set used when no model file (or a model file with no
+    /// `TEMPLATES` line) has been loaded, matching the hand-written features
+    /// this model shipped with before templates were configurable.
+    const DEFAULT_FEATURE_TEMPLATES: &'static [&'static str] = &[
+        "token", "length", "is_upper", "is_digit", "first_char", "last_char",
+        "prev_token", "next_token", "prev_current", "current_next",
+    ];
+
+    /// Pseudo-label standing in for "before the first token" on the
+    /// left-hand side of a transition key, so a sequence's start transitions
+    /// (`start_trans[s]` in the Viterbi recurrence) live in the same
+    /// `weights` map as ordinary `prev#curr` transitions instead of needing
+    /// a dedicated field.
+    const START_LABEL: &'static str = "<START>";
+    /// Pseudo-label standing in for "after the last token", analogous to
+    /// `START_LABEL` but for `end_trans[s]`.
+    const END_LABEL: &'static str = "<END>";
 
     fn extract_features(&self, tokens: &[String], position: usize) -> Vec<String> {
         let mut features = Vec::new();
         let current_token = &tokens[position];
-        
-        // Current token features
-        features.push(format!("token={}", current_token));
-        features.push(format!("length={}", current_token.len()));
-        features.push(format!("is_upper={}", current_token.chars().all(|c| c.is_uppercase())));
-        features.push(format!("is_digit={}", current_token.chars().all(|c| c.is_ascii_digit())));
-        
-        // Character-level features
-        if let Some(first_char) = current_token.chars().next() {
-            features.push(format!("first_char={}", first_char));
-        }
-        if let Some(last_char) = current_token.chars().last() {
-            features.push(format!("last_char={}", last_char));
-        }
-        
-        // Context features
-        if position > 0 {
-            features.push(format!("prev_token={}", tokens[position - 1]));
-        } else {
-            features.push("prev_token=<BOS>".to_string());
-        }
-        
-        if position < tokens.len() - 1 {
-            features.push(format!("next_token={}", tokens[position + 1]));
+
+        let templates: &[String];
+        let owned;
+        if self.label_set.feature_templates().is_empty() {
+            owned = Self::DEFAULT_FEATURE_TEMPLATES.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+            templates = &owned;
         } else {
-            features.push("next_token=<EOS>".to_string());
+            templates = self.label_set.feature_templates();
         }
-        
-        // Bigram features
-        if position > 0 {
-            features.push(format!("prev_current={}_{}", tokens[position - 1], current_token));
-        }
-        if position < tokens.len() - 1 {
-            features.push(format!("current_next={}_{}", current_token, tokens[position + 1]));
+
+        for template in templates {
+            match template.as_str() {
+                "token" => features.push(format!("token={}", current_token)),
+                "length" => features.push(format!("length={}", current_token.len())),
+                "is_upper" => features.push(format!("is_upper={}", current_token.chars().all(|c| c.is_uppercase()))),
+                "is_digit" => features.push(format!("is_digit={}", current_token.chars().all(|c| c.is_ascii_digit()))),
+                "first_char" => {
+                    if let Some(first_char) = current_token.chars().next() {
+                        features.push(format!("first_char={}", first_char));
+                    }
+                }
+                "last_char" => {
+                    if let Some(last_char) = current_token.chars().last() {
+                        features.push(format!("last_char={}", last_char));
+                    }
+                }
+                "prev_token" => {
+                    if position > 0 {
+                        features.push(format!("prev_token={}", tokens[position - 1]));
+                    } else {
+                        features.push("prev_token=<BOS>".to_string());
+                    }
+                }
+                "next_token" => {
+                    if position < tokens.len() - 1 {
+                        features.push(format!("next_token={}", tokens[position + 1]));
+                    } else {
+                        features.push("next_token=<EOS>".to_string());
+                    }
+                }
+                "prev_current" => {
+                    if position > 0 {
+                        features.push(format!("prev_current={}_{}", tokens[position - 1], current_token));
+                    }
+                }
+                "current_next" => {
+                    if position < tokens.len() - 1 {
+                        features.push(format!("current_next={}_{}", current_token, tokens[position + 1]));
+                    }
+                }
+                // Word-shape and affix features a POS/chunk model needs that
+                // plain word-boundary tagging never did.
+                "shape" => features.push(format!("shape={}", word_shape(current_token))),
+                "prefix1" => features.push(format!("prefix1={}", char_prefix(current_token, 1))),
+                "prefix2" => features.push(format!("prefix2={}", char_prefix(current_token, 2))),
+                "prefix3" => features.push(format!("prefix3={}", char_prefix(current_token, 3))),
+                "suffix1" => features.push(format!("suffix1={}", char_suffix(current_token, 1))),
+                "suffix2" => features.push(format!("suffix2={}", char_suffix(current_token, 2))),
+                "suffix3" => features.push(format!("suffix3={}", char_suffix(current_token, 3))),
+                "contains_digit" => features.push(format!("contains_digit={}", current_token.chars().any(|c| c.is_ascii_digit()))),
+                "contains_hyphen" => features.push(format!("contains_hyphen={}", current_token.contains('-'))),
+                // Unknown template names are ignored rather than rejected, so a
+                // model trained with an extra template this build doesn't know
+                // about still loads and scores with the templates it does know.
+                _ => {}
+            }
         }
-        
+
         features
     }
 
@@ -243,106 +743,425 @@ impl CRFModel {
         score
     }
 
+    /// Sum of the learned weights of `feature#label` for every active
+    /// feature at this position. A feature this model never saw weighted
+    /// (an unseen feature, or one the loaded model has no weight for)
+    /// contributes zero rather than erroring.
+    fn emission_score(&self, features: &[String], label: &str) -> f64 {
+        features
+            .iter()
+            .filter_map(|feature| self.weights.get(&format!("{}#{}", feature, label)))
+            .sum()
+    }
+
+    /// Learned weight of the `prev_label -> label` transition, or zero if
+    /// that transition was never weighted.
+    fn transition_score(&self, prev_label: &str, label: &str) -> f64 {
+        *self.weights.get(&format!("{}#{}", prev_label, label)).unwrap_or(&0.0)
+    }
+
+    /// `start_trans[label]`: learned weight of starting a sequence in
+    /// `label`, stored as an ordinary transition out of `START_LABEL`.
+    fn start_transition_score(&self, label: &str) -> f64 {
+        self.transition_score(Self::START_LABEL, label)
+    }
+
+    /// `end_trans[label]`: learned weight of ending a sequence in `label`,
+    /// stored as an ordinary transition into `END_LABEL`.
+    fn end_transition_score(&self, label: &str) -> f64 {
+        self.transition_score(label, Self::END_LABEL)
+    }
+
+    /// Linear-chain CRF Viterbi decoding: find the single highest-scoring
+    /// label sequence under the recurrence
+    /// `delta[t][s] = emit(s, features[t]) + max_s' (delta[t-1][s'] + trans[s'][s])`,
+    /// with `delta[0][s]` seeded from `start_trans[s]` instead of a
+    /// predecessor, and `end_trans[s]` folded in when picking the final
+    /// state. Enforcing transitions (rather than tagging each position's
+    /// emission-only argmax, as the old implementation did) is what keeps
+    /// adjacent labels coherent, e.g. ruling out an `I-W` with no preceding
+    /// `B-W` once the loaded model's transition weights penalize it.
     fn viterbi_decode(&self, tokens: &[String]) -> Vec<String> {
         if tokens.is_empty() {
             return Vec::new();
         }
-        
+
         let n_tokens = tokens.len();
-        let n_labels = self.labels.len();
-        
-        // DP table: dp[i][j] = best score for assigning label j to token i
-        let mut dp = vec![vec![f64::NEG_INFINITY; n_labels]; n_tokens];
-        let mut backtrack = vec![vec![0; n_labels]; n_tokens];
-        
-        // Initialize first token
-        for (j, label) in self.labels.iter().enumerate() {
-            let features = self.extract_features(tokens, 0);
-            let mut score = 0.0;
-            for feature in features {
-                let feature_tag = format!("{}#{}", feature, label);
-                if let Some(&weight) = self.weights.get(&feature_tag) {
-                    score += weight;
-                }
-            }
-            dp[0][j] = score;
+        let labels = self.label_set.labels();
+        let n_labels = labels.len();
+
+        // delta[i][j] = best score of any path ending at token i in label j
+        let mut delta = vec![vec![f64::NEG_INFINITY; n_labels]; n_tokens];
+        let mut backpointer = vec![vec![0usize; n_labels]; n_tokens];
+
+        let first_features = self.extract_features(tokens, 0);
+        for (j, label) in labels.iter().enumerate() {
+            delta[0][j] = self.emission_score(&first_features, label) + self.start_transition_score(label);
         }
-        
-        // Forward pass
+
         for i in 1..n_tokens {
             let features = self.extract_features(tokens, i);
-            
-            for (j, curr_label) in self.labels.iter().enumerate() {
-                let mut emission_score = 0.0;
-                for feature in &features {
-                    let feature_tag = format!("{}#{}", feature, curr_label);
-                    if let Some(&weight) = self.weights.get(&feature_tag) {
-                        emission_score += weight;
-                    }
-                }
-                
-                for (k, prev_label) in self.labels.iter().enumerate() {
-                    let transition = format!("{}#{}", prev_label, curr_label);
-                    let transition_score = self.weights.get(&transition).unwrap_or(&0.0);
-                    
-                    let total_score = dp[i-1][k] + transition_score + emission_score;
-                    
-                    if total_score > dp[i][j] {
-                        dp[i][j] = total_score;
-                        backtrack[i][j] = k;
+
+            for (j, curr_label) in labels.iter().enumerate() {
+                let emission = self.emission_score(&features, curr_label);
+
+                for (k, prev_label) in labels.iter().enumerate() {
+                    let score = delta[i - 1][k] + self.transition_score(prev_label, curr_label) + emission;
+
+                    if score > delta[i][j] {
+                        delta[i][j] = score;
+                        backpointer[i][j] = k;
                     }
                 }
             }
         }
-        
-        // Find best final state
+
+        // Fold in end_trans[s] only when choosing the final state; it isn't
+        // part of delta[T-1][s] itself, matching the recurrence's "add
+        // end_trans[s] at the final position" step.
         let mut best_final_state = 0;
-        let mut best_score = dp[n_tokens - 1][0];
-        for j in 1..n_labels {
-            if dp[n_tokens - 1][j] > best_score {
-                best_score = dp[n_tokens - 1][j];
+        let mut best_score = f64::NEG_INFINITY;
+        for (j, label) in labels.iter().enumerate() {
+            let score = delta[n_tokens - 1][j] + self.end_transition_score(label);
+            if score > best_score {
+                best_score = score;
                 best_final_state = j;
             }
         }
-        
-        // Backtrack to get the best sequence
-        let mut result = vec![0; n_tokens];
+
+        let mut result = vec![0usize; n_tokens];
         result[n_tokens - 1] = best_final_state;
-        
+
         for i in (1..n_tokens).rev() {
-            result[i - 1] = backtrack[i][result[i]];
+            result[i - 1] = backpointer[i][result[i]];
         }
-        
-        result.into_iter().map(|i| self.labels[i].clone()).collect()
+
+        result.into_iter().map(|i| labels[i].clone()).collect()
+    }
+
+    /// Core of `beam_decode`, returning raw (un-normalized) cumulative
+    /// log-probabilities instead of a probability distribution, so callers
+    /// that need to fold in an externally-scored sequence (see
+    /// `predict_with_scores`) can renormalize over a candidate set that
+    /// includes it.
+    ///
+    /// At each position, emission scores for every label are converted to
+    /// probabilities with a softmax, each surviving beam is expanded by every
+    /// label, and the beam is pruned back down to `beam_width` sequences
+    /// ranked by cumulative log-probability. A min-heap over `Reverse` log-prob
+    /// lets pruning just pop the single worst survivor instead of re-sorting.
+    fn beam_decode_raw(&self, tokens: &[String], beam_width: usize, n_best: usize) -> Vec<(Vec<String>, f64)> {
+        if tokens.is_empty() {
+            return Vec::new();
+        }
+
+        let beam_width = beam_width.max(1);
+        let mut beams: Vec<BeamSequence> = vec![BeamSequence {
+            tags: Vec::new(),
+            log_prob: 0.0,
+        }];
+
+        let labels = self.label_set.labels();
+        for position in 0..tokens.len() {
+            let features = self.extract_features(tokens, position);
+            let emission_scores: Vec<f64> = labels
+                .iter()
+                .map(|label| self.emission_score(&features, label))
+                .collect();
+            let emission_probs = softmax(&emission_scores);
+
+            let mut heap: BinaryHeap<Reverse<BeamSequence>> = BinaryHeap::new();
+            for beam in &beams {
+                for (label_idx, label) in labels.iter().enumerate() {
+                    let transition_score = beam
+                        .tags
+                        .last()
+                        .map(|prev_tag| self.transition_score(prev_tag, label))
+                        .unwrap_or(0.0);
+
+                    let mut tags = beam.tags.clone();
+                    tags.push(label.clone());
+                    let log_prob = beam.log_prob + emission_probs[label_idx].ln() + transition_score;
+
+                    heap.push(Reverse(BeamSequence { tags, log_prob }));
+                    if heap.len() > beam_width {
+                        heap.pop(); // evicts the current worst survivor
+                    }
+                }
+            }
+
+            beams = heap.into_iter().map(|Reverse(seq)| seq).collect();
+        }
+
+        beams.sort_by(|a, b| b.log_prob.partial_cmp(&a.log_prob).unwrap_or(Ordering::Equal));
+        beams.truncate(n_best.max(1));
+        beams.into_iter().map(|b| (b.tags, b.log_prob)).collect()
+    }
+
+    /// The same cumulative log-probability `beam_decode_raw` would have
+    /// assigned `tags`, had the beam happened to carry it as a survivor at
+    /// every position. Lets `predict_with_scores` compare the Viterbi path
+    /// against the beam's candidates on a common scale even when the beam
+    /// itself pruned that path away.
+    fn beam_style_log_prob(&self, tokens: &[String], tags: &[String]) -> f64 {
+        let labels = self.label_set.labels();
+        let mut log_prob = 0.0;
+        let mut prev_tag: Option<&str> = None;
+
+        for (position, tag) in tags.iter().enumerate() {
+            let features = self.extract_features(tokens, position);
+            let emission_scores: Vec<f64> = labels
+                .iter()
+                .map(|label| self.emission_score(&features, label))
+                .collect();
+            let emission_probs = softmax(&emission_scores);
+            let label_idx = labels.iter().position(|label| label == tag).unwrap_or(0);
+            let transition_score = prev_tag.map(|prev| self.transition_score(prev, tag)).unwrap_or(0.0);
+
+            log_prob += emission_probs[label_idx].ln() + transition_score;
+            prev_tag = Some(tag.as_str());
+        }
+
+        log_prob
+    }
+
+    /// OpenNLP-style beam-search decoder: returns the top `n_best` tag
+    /// sequences with real (normalized) probabilities, instead of just the
+    /// single best path `viterbi_decode` returns. See `beam_decode_raw` for
+    /// how each sequence is scored.
+    pub fn beam_decode(&self, tokens: &[String], beam_width: usize, n_best: usize) -> Vec<(Vec<String>, f64)> {
+        normalize_log_probs(self.beam_decode_raw(tokens, beam_width, n_best))
+    }
+
+    /// Like `predict`, but pairs each tag with a per-token confidence: the
+    /// fraction of probability mass assigned to candidate sequences that
+    /// agree with `viterbi_decode`'s choice at that position, among
+    /// `beam_decode`'s candidates plus the Viterbi path itself. Seeding the
+    /// Viterbi path in guarantees it always carries positive mass even when
+    /// the beam (whose search is greedy and can be narrower than the true
+    /// label space) prunes it away before `viterbi_decode`'s pick would have
+    /// been confirmed a survivor — otherwise the model's own top choice could
+    /// be reported with 0.0 confidence. A token every candidate agrees on
+    /// scores 1.0; one where the candidates are split scores lower — e.g. to
+    /// threshold which NER spans a caller should trust.
+    pub fn predict_with_scores(&self, tokens: &[String], beam_width: usize) -> Vec<(String, f64)> {
+        if !self.loaded {
+            panic!("Model not loaded. Call load() first.");
+        }
+
+        if tokens.is_empty() {
+            return Vec::new();
+        }
+
+        let best_tags = self.viterbi_decode(tokens);
+        let beam_width = beam_width.max(1);
+        let mut candidates = self.beam_decode_raw(tokens, beam_width, beam_width);
+
+        if !candidates.iter().any(|(tags, _)| tags == &best_tags) {
+            let log_prob = self.beam_style_log_prob(tokens, &best_tags);
+            candidates.push((best_tags.clone(), log_prob));
+        }
+
+        let candidates = normalize_log_probs(candidates);
+
+        best_tags
+            .into_iter()
+            .enumerate()
+            .map(|(i, tag)| {
+                let confidence: f64 = candidates
+                    .iter()
+                    .filter(|(tags, _)| tags.get(i) == Some(&tag))
+                    .map(|(_, prob)| prob)
+                    .sum();
+                (tag, confidence)
+            })
+            .collect()
+    }
+}
+
+/// Turn cumulative log-probabilities into a real probability distribution
+/// over exactly the given candidates (not the full, possibly-pruned beam
+/// they were drawn from).
+fn normalize_log_probs(candidates: Vec<(Vec<String>, f64)>) -> Vec<(Vec<String>, f64)> {
+    let max_log_prob = candidates
+        .iter()
+        .map(|(_, log_prob)| *log_prob)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let total: f64 = candidates.iter().map(|(_, log_prob)| (log_prob - max_log_prob).exp()).sum();
+
+    candidates
+        .into_iter()
+        .map(|(tags, log_prob)| {
+            let prob = if total > 0.0 { (log_prob - max_log_prob).exp() / total } else { 0.0 };
+            (tags, prob)
+        })
+        .collect()
+}
+
+/// A partial beam-search hypothesis: the tag sequence chosen so far and its
+/// cumulative log-probability.
+#[derive(Debug, Clone)]
+struct BeamSequence {
+    tags: Vec<String>,
+    log_prob: f64,
+}
+
+impl PartialEq for BeamSequence {
+    fn eq(&self, other: &Self) -> bool {
+        self.log_prob == other.log_prob
+    }
+}
+impl Eq for BeamSequence {}
+
+impl PartialOrd for BeamSequence {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for BeamSequence {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.log_prob.partial_cmp(&other.log_prob).unwrap_or(Ordering::Equal)
+    }
+}
+
+/// Coarse word-shape feature: uppercase letters become `X`, lowercase
+/// (including accented Vietnamese vowels, which are themselves lowercase
+/// codepoints) become `x`, digits become `d`, everything else (punctuation,
+/// hyphens) is kept as-is. Distinguishes e.g. a capitalized name ("Hà" ->
+/// "Xx") from an all-lowercase word ("hà" -> "xx").
+fn word_shape(token: &str) -> String {
+    token
+        .chars()
+        .map(|c| {
+            if c.is_ascii_digit() {
+                'd'
+            } else if c.is_uppercase() {
+                'X'
+            } else if c.is_lowercase() {
+                'x'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// First `n` characters of `token` (fewer if it's shorter), used for
+/// prefix n-gram features.
+fn char_prefix(token: &str, n: usize) -> String {
+    token.chars().take(n).collect()
+}
+
+/// Last `n` characters of `token` (fewer if it's shorter), used for
+/// suffix n-gram features.
+fn char_suffix(token: &str, n: usize) -> String {
+    let chars: Vec<char> = token.chars().collect();
+    let start = chars.len().saturating_sub(n);
+    chars[start..].iter().collect()
+}
+
+fn softmax(scores: &[f64]) -> Vec<f64> {
+    if scores.is_empty() {
+        return Vec::new();
     }
+    let max = scores.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let exps: Vec<f64> = scores.iter().map(|score| (score - max).exp()).collect();
+    let sum: f64 = exps.iter().sum();
+    exps.iter().map(|exp| exp / sum).collect()
 }
 
-impl SequenceTagger for CRFModel {
+impl<L: LabelSet> SequenceTagger for CrfTagger<L> {
+    /// Load weights from a CRFsuite-style text dump.
+    ///
+    /// The file is plain text, one directive or weight per line. Blank lines
+    /// and lines starting with `#` are ignored. Two directive lines, each
+    /// tab-separated, configure the label set and the feature templates that
+    /// `extract_features` will compute:
+    ///
+    /// ```text
+    /// LABELS	B-W	I-W
+    /// TEMPLATES	token	prev_token	next_token
+    /// ```
+    ///
+    /// Every remaining non-directive line is a `key<TAB>weight` pair, where
+    /// `key` is either `feature#label` (an emission weight) or `prev#curr`
+    /// (a transition weight) — exactly the keys `score_sequence` and
+    /// `viterbi_decode` look up. The pseudo-labels `<START>` and `<END>` may
+    /// stand in for `prev`/`curr` to weight `start_trans[s]`/`end_trans[s]`
+    /// (e.g. `<START>#B-W` or `I-W#<END>`), letting Viterbi decoding favor
+    /// starting/ending a sequence in particular labels without a dedicated
+    /// field alongside `weights`. `LABELS` may appear in any order relative
+    /// to the weight lines, but at least one is required; every ordinary
+    /// label named on the right-hand side of a weight key (or the left-hand
+    /// side of an end-transition key) must appear in `LABELS`, or the file
+    /// is rejected as malformed. A missing `TEMPLATES` line falls back to
+    /// this model's built-in default templates.
     fn load(&mut self, model_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
-        // In a real implementation, you would load the CRF model from a file
-        // For this example, we'll create some dummy weights
-        
         if !model_path.exists() {
-            eprintln!("Warning: Model path {:?} does not exist. Using dummy model.", model_path);
+            return Err(format!("CRF model file not found: {:?}", model_path).into());
         }
-        
-        // Dummy weights for demonstration
-        // In reality, these would be learned from training data
-        self.weights.insert("token=Bác#B-W".to_string(), 2.0);
-        self.weights.insert("token=sĩ#I-W".to_string(), 1.5);
-        self.weights.insert("token=bây#B-W".to_string(), 1.8);
-        self.weights.insert("token=giờ#I-W".to_string(), 1.3);
-        self.weights.insert("token=có#B-W".to_string(), 1.9);
-        self.weights.insert("token=thể#I-W".to_string(), 1.4);
-        self.weights.insert("B-W#I-W".to_string(), 1.0);
-        self.weights.insert("I-W#B-W".to_string(), 0.5);
-        self.weights.insert("B-W#B-W".to_string(), 0.3);
-        self.weights.insert("I-W#I-W".to_string(), 0.8);
-        
+
+        let text = fs::read_to_string(model_path)?;
+
+        let mut labels: Vec<String> = Vec::new();
+        let mut feature_templates: Vec<String> = Vec::new();
+        let mut weights: HashMap<String, f64> = HashMap::new();
+
+        for (line_no, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.split('\t');
+            let head = fields.next().unwrap_or_default();
+            match head {
+                "LABELS" => labels = fields.map(|s| s.to_string()).collect(),
+                "TEMPLATES" => feature_templates = fields.map(|s| s.to_string()).collect(),
+                _ => {
+                    let weight_str = fields.next().ok_or_else(|| {
+                        format!("malformed CRF model file at line {}: expected `key<TAB>weight`, got {:?}", line_no + 1, line)
+                    })?;
+                    let weight: f64 = weight_str.trim().parse().map_err(|_| {
+                        format!("malformed CRF model file at line {}: weight {:?} is not a number", line_no + 1, weight_str)
+                    })?;
+                    weights.insert(head.to_string(), weight);
+                }
+            }
+        }
+
+        if labels.is_empty() {
+            return Err(format!("malformed CRF model file {:?}: no LABELS line", model_path).into());
+        }
+
+        for key in weights.keys() {
+            if let Some((prev, curr)) = key.rsplit_once('#') {
+                if curr == Self::END_LABEL {
+                    if !labels.iter().any(|l| l == prev) {
+                        return Err(format!(
+                            "malformed CRF model file {:?}: end-transition key {:?} references unknown label {:?}",
+                            model_path, key, prev
+                        ).into());
+                    }
+                    continue;
+                }
+            }
+
+            let label = key.rsplit('#').next().unwrap_or(key);
+            if !labels.iter().any(|l| l == label) {
+                return Err(format!(
+                    "malformed CRF model file {:?}: weight key {:?} references unknown label {:?}",
+                    model_path, key, label
+                ).into());
+            }
+        }
+
+        self.label_set = L::from_loaded(labels, feature_templates);
+        self.weights = weights;
         self.loaded = true;
         Ok(())
     }
-    
+
     fn predict(&self, features: &[Vec<String>]) -> Vec<String> {
         if !self.loaded {
             panic!("Model not loaded. Call load() first.");
@@ -357,16 +1176,27 @@ impl SequenceTagger for CRFModel {
     }
 }
 
+/// Thin wrapper instantiating `CrfTagger` with the word-boundary label set,
+/// i.e. the model `VietnameseWordSegmenter` runs.
 pub struct FastCRFSequenceTagger {
-    model: CRFModel,
+    model: CrfTagger<WordBoundaryLabels>,
 }
 
 impl FastCRFSequenceTagger {
     pub fn new() -> Self {
         Self {
-            model: CRFModel::new(),
+            model: CrfTagger::new(),
         }
     }
+
+    pub fn beam_decode(
+        &self,
+        tokens: &[String],
+        beam_width: usize,
+        n_best: usize,
+    ) -> Vec<(Vec<String>, f64)> {
+        self.model.beam_decode(tokens, beam_width, n_best)
+    }
 }
 
 impl SequenceTagger for FastCRFSequenceTagger {
@@ -383,9 +1213,19 @@ impl SequenceTagger for FastCRFSequenceTagger {
 static WORD_TOKENIZE_MODEL: Lazy<Arc<Mutex<Option<FastCRFSequenceTagger>>>> = 
     Lazy::new(|| Arc::new(Mutex::new(None)));
 
+#[derive(Serialize, Deserialize)]
 pub struct VietnameseWordSegmenter {
     tokenizer: VietnameseTokenizer,
     model_path: Option<PathBuf>,
+    /// When set, segmentation runs the dictionary/DAG engine instead of the
+    /// CRF model (see `with_dictionary`).
+    #[serde(skip)]
+    dictionary: Option<Arc<WordDictionary>>,
+    /// When set, every syllable is run through `SpellChecker::correct`
+    /// before segmentation, repairing mis-typed syllables in place (see
+    /// `set_spell_checker`).
+    #[serde(skip)]
+    spell_checker: Option<Arc<SpellChecker>>,
 }
 
 impl VietnameseWordSegmenter {
@@ -393,23 +1233,128 @@ impl VietnameseWordSegmenter {
         Self {
             tokenizer: VietnameseTokenizer::new(),
             model_path: None,
+            dictionary: None,
+            spell_checker: None,
         }
     }
-    
+
     pub fn with_model_path<P: AsRef<Path>>(model_path: P) -> Self {
         Self {
             tokenizer: VietnameseTokenizer::new(),
             model_path: Some(model_path.as_ref().to_path_buf()),
+            dictionary: None,
+            spell_checker: None,
         }
     }
-    
+
     pub fn with_fixed_words(fixed_words: &[String]) -> Self {
         Self {
             tokenizer: VietnameseTokenizer::with_fixed_words(fixed_words),
             model_path: None,
+            dictionary: None,
+            spell_checker: None,
         }
     }
-    
+
+    /// Like `with_fixed_words`, but with full per-entry control over
+    /// matching behavior (see `AddedToken` and
+    /// `VietnameseTokenizer::with_fixed_tokens`).
+    pub fn with_fixed_tokens(fixed_tokens: &[AddedToken]) -> Self {
+        Self {
+            tokenizer: VietnameseTokenizer::with_fixed_tokens(fixed_tokens),
+            model_path: None,
+            dictionary: None,
+            spell_checker: None,
+        }
+    }
+
+    /// Register special tokens (`<s>`, `</s>`, `<mask>`, ...) that must never
+    /// be split during tokenization and are never touched by normalization.
+    pub fn add_special_tokens(&mut self, tokens: &[String]) {
+        self.tokenizer.add_special_tokens(tokens);
+    }
+
+    /// Register added tokens with full control over matching/stripping
+    /// behavior (see `AddedToken`).
+    pub fn add_added_tokens(&mut self, tokens: Vec<AddedToken>) {
+        self.tokenizer.add_added_tokens(tokens);
+    }
+
+    /// Repoint an already-registered added token's surface content to `new`
+    /// without changing its id slot. Returns `false` if `old` was never
+    /// registered.
+    pub fn assign_token(&mut self, old: &str, new: &str) -> bool {
+        self.tokenizer.assign_token(old, new)
+    }
+
+    /// Override how much normalization runs before matching (see
+    /// `NormalizationMode`); callers whose input is already normalized can
+    /// pass `NormalizationMode::None` to skip the pass entirely.
+    pub fn set_normalization_mode(&mut self, mode: NormalizationMode) {
+        self.tokenizer.set_normalization_mode(mode);
+    }
+
+    /// Build a segmenter driven by a frequency-weighted word dictionary
+    /// instead of the CRF model: segmentation becomes a maximum-probability
+    /// lattice/Viterbi search over dictionary entries that never merges
+    /// across a non-`Word` token (see `dictionary::segment_tokens_with_dictionary`),
+    /// with `user_words` inserted at a high frequency so they win ties.
+    pub fn with_dictionary<P: AsRef<Path>>(
+        dictionary_path: P,
+        user_words: &[String],
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut dictionary = WordDictionary::load_from_file(dictionary_path)?;
+        dictionary.add_user_words(user_words);
+        Ok(Self {
+            tokenizer: VietnameseTokenizer::new(),
+            model_path: None,
+            dictionary: Some(Arc::new(dictionary)),
+            spell_checker: None,
+        })
+    }
+
+    /// Repair mis-typed syllables against `spell_checker` before segmentation
+    /// runs, using its symmetric-delete correction index (see
+    /// `SpellChecker::correct`). A syllable with no correction candidate, or
+    /// already known to the checker, passes through unchanged.
+    pub fn set_spell_checker(&mut self, spell_checker: SpellChecker) {
+        self.spell_checker = Some(Arc::new(spell_checker));
+    }
+
+    fn apply_spell_correction(&self, tokens: Vec<String>) -> Vec<String> {
+        let Some(spell_checker) = &self.spell_checker else {
+            return tokens;
+        };
+        tokens
+            .into_iter()
+            .map(|token| {
+                spell_checker
+                    .correct(&token)
+                    .into_iter()
+                    .next()
+                    .unwrap_or(token)
+            })
+            .collect()
+    }
+
+    /// Like `apply_spell_correction`, but corrects a `Token`'s `text` in
+    /// place instead of a bare string, so its `token_type`/offsets survive
+    /// for callers that need them (e.g. `segment_tokens_with_dictionary`).
+    fn apply_spell_correction_tokens(&self, tokens: Vec<Token>) -> Vec<Token> {
+        let Some(spell_checker) = &self.spell_checker else {
+            return tokens;
+        };
+        tokens
+            .into_iter()
+            .map(|mut token| {
+                if let Some(corrected) = spell_checker.correct(&token.text).into_iter().next() {
+                    token.text = corrected;
+                }
+                token
+            })
+            .collect()
+    }
+
     fn ensure_model_loaded(&self) {
         let mut global_model = WORD_TOKENIZE_MODEL.lock().unwrap();
         
@@ -420,12 +1365,15 @@ impl VietnameseWordSegmenter {
                 .map(|p| p.as_path())
                 .unwrap_or_else(|| Path::new("models/ws_crf_vlsp2013_20230727"));
             
-            if let Err(e) = model.load(model_path) {
-                eprintln!("Warning: Failed to load model from {:?}: {}", model_path, e);
-                // Continue with dummy model for demonstration
+            match model.load(model_path) {
+                Ok(()) => *global_model = Some(model),
+                Err(e) => {
+                    eprintln!("Warning: Failed to load model from {:?}: {}", model_path, e);
+                    // Leave `global_model` as `None` so callers fall back to
+                    // the trivial all-`B-W` tagging below instead of calling
+                    // `predict` on a model that never finished loading.
+                }
             }
-            
-            *global_model = Some(model);
         }
     }
     
@@ -448,46 +1396,39 @@ impl VietnameseWordSegmenter {
         use_token_normalize: bool,
         fixed_words: &[String],
     ) -> Vec<String> {
-        self.ensure_model_loaded();
-        
         // Use tokenizer with appropriate settings
         let mut tokenizer = if fixed_words.is_empty() {
             self.tokenizer.clone()
         } else {
             VietnameseTokenizer::with_fixed_words(fixed_words)
         };
-        
+
         tokenizer.set_token_normalize(use_token_normalize);
-        let tokens = tokenizer.tokenize(sentence);
-        
-        // Prepare features for CRF model
-        let features: Vec<Vec<String>> = tokens.iter()
-            .map(|token| vec![token.clone()])
-            .collect();
-        
-        // Get predictions from CRF model
-        let global_model = WORD_TOKENIZE_MODEL.lock().unwrap();
-        let tags = if let Some(ref model) = *global_model {
-            model.predict(&features)
-        } else {
-            // Fallback: assume all tokens are beginning of words
-            vec!["B-W".to_string(); tokens.len()]
-        };
-        
-        // Combine tokens based on tags
-        let mut output = Vec::new();
-        for (tag, token) in tags.iter().zip(tokens.iter()) {
-            if tag == "I-W" && !output.is_empty() {
-                // Continue previous word
-                if let Some(last_word) = output.last_mut() {
-                    *last_word = format!("{} {}", last_word, token);
-                }
+
+        let output = if let Some(dictionary) = &self.dictionary {
+            let tagged_tokens = self.apply_spell_correction_tokens(tokenizer.tokenize_with_tags(sentence, false));
+            segment_tokens_with_dictionary(dictionary, &tagged_tokens)
+        } else {
+            let tokens = self.apply_spell_correction(tokenizer.tokenize(sentence));
+            self.ensure_model_loaded();
+
+            // Prepare features for CRF model
+            let features: Vec<Vec<String>> = tokens.iter()
+                .map(|token| vec![token.clone()])
+                .collect();
+
+            // Get predictions from CRF model
+            let global_model = WORD_TOKENIZE_MODEL.lock().unwrap();
+            let tags = if let Some(ref model) = *global_model {
+                model.predict(&features)
             } else {
-                // Start new word
-                output.push(token.clone());
-            }
-        }
-        
+                // Fallback: assume all tokens are beginning of words
+                vec!["B-W".to_string(); tokens.len()]
+            };
+
+            merge_tokens_by_tags(&tokens, &tags)
+        };
+
         if format_as_text {
             vec![output.iter()
                 .map(|word| word.replace(' ', "_"))
@@ -497,21 +1438,203 @@ impl VietnameseWordSegmenter {
             output
         }
     }
+
+    /// Like `word_tokenize`, but returns the top `n_best` segmentations from
+    /// the CRF model's beam search, each paired with a normalized probability.
+    /// Falls back to a single all-`B-W` segmentation (probability 1.0) when no
+    /// model is loaded, matching `word_tokenize_with_options`'s fallback.
+    pub fn word_tokenize_nbest(
+        &self,
+        sentence: &str,
+        beam_width: usize,
+        n_best: usize,
+    ) -> Vec<(Vec<String>, f64)> {
+        self.ensure_model_loaded();
+
+        let tokens = self.tokenizer.tokenize(sentence);
+
+        let global_model = WORD_TOKENIZE_MODEL.lock().unwrap();
+        let tag_sequences = if let Some(ref model) = *global_model {
+            model.beam_decode(&tokens, beam_width, n_best)
+        } else {
+            vec![(vec!["B-W".to_string(); tokens.len()], 1.0)]
+        };
+
+        tag_sequences
+            .into_iter()
+            .map(|(tags, prob)| (merge_tokens_by_tags(&tokens, &tags), prob))
+            .collect()
+    }
+}
+
+/// Merge flat tokens into words according to B-W/I-W tags: an `I-W` token
+/// continues the previous word (space-joined), anything else starts a new one.
+fn merge_tokens_by_tags(tokens: &[String], tags: &[String]) -> Vec<String> {
+    let mut output = Vec::new();
+    for (tag, token) in tags.iter().zip(tokens.iter()) {
+        if tag == "I-W" && !output.is_empty() {
+            if let Some(last_word) = output.last_mut() {
+                *last_word = format!("{} {}", last_word, token);
+            }
+        } else {
+            output.push(token.clone());
+        }
+    }
+    output
 }
 
 impl Clone for VietnameseTokenizer {
     fn clone(&self) -> Self {
+        let (normalized_fixed_matcher, normalized_fixed_words) =
+            build_fixed_word_matcher_for(&self.fixed_words, true, self.options.fold_diacritics);
+        let (raw_fixed_matcher, raw_fixed_words) =
+            build_fixed_word_matcher_for(&self.fixed_words, false, self.options.fold_diacritics);
         Self {
-            patterns: build_regex_patterns(&[]), // Rebuild patterns
-            use_character_normalize: self.use_character_normalize,
+            patterns: build_regex_patterns(&self.options),
+            fixed_words: self.fixed_words.clone(),
+            normalized_fixed_matcher,
+            normalized_fixed_words,
+            raw_fixed_matcher,
+            raw_fixed_words,
+            normalization_mode: self.normalization_mode,
             use_token_normalize: self.use_token_normalize,
+            old_style_tone: self.old_style_tone,
+            options: self.options,
+            added_vocabulary: self.added_vocabulary.clone(),
         }
     }
 }
 
-fn build_regex_patterns(fixed_words: &[String]) -> Regex {
+/// Build the Aho-Corasick automaton for the subset of `fixed_words` whose
+/// `normalized` flag matches `normalized`, plus that subset itself (in the
+/// same pattern order the automaton was built with, so a match's
+/// `Match::pattern()` index looks its originating `AddedToken` back up).
+/// Returns `(None, Vec::new())` if the subset is empty. Leftmost-longest
+/// match kind makes `find_iter` favor the longest fixed phrase at each
+/// position (so e.g. "Viện nghiên cứu khoa học" wins over a shorter fixed
+/// entry that's a prefix of it) and never report overlapping matches, which
+/// is exactly the non-overlapping, longest-wins span set `tokenize_with_tags`
+/// carves fixed-word tokens out of.
+/// When `fold_diacritics` is set, the automaton is built over each entry's
+/// `fold`ed content instead of its raw content, so it must then be matched
+/// against `fold`ed haystack text too (see `fixed_word_spans_for`).
+fn build_fixed_word_matcher_for(
+    fixed_words: &[AddedToken],
+    normalized: bool,
+    fold_diacritics: bool,
+) -> (Option<AhoCorasick>, Vec<AddedToken>) {
+    let subset: Vec<AddedToken> = fixed_words
+        .iter()
+        .filter(|token| token.normalized == normalized)
+        .cloned()
+        .collect();
+
+    if subset.is_empty() {
+        return (None, subset);
+    }
+
+    let patterns: Vec<String> = subset
+        .iter()
+        .map(|token| if fold_diacritics { fold(&token.content) } else { token.content.clone() })
+        .collect();
+    let matcher = AhoCorasick::builder()
+        .match_kind(MatchKind::LeftmostLongest)
+        .build(&patterns)
+        .expect("failed to build fixed-word automaton");
+
+    (Some(matcher), subset)
+}
+
+/// Byte spans of every match `matcher` finds in `haystack`, adjusted per the
+/// originating `AddedToken`'s behavior: filtered out if `single_word` is set
+/// and the match doesn't fall on a word boundary (so e.g. a fixed entry "an"
+/// doesn't fire inside "quan"), then expanded to absorb adjacent whitespace
+/// if `lstrip`/`rstrip` is set. `tokens` must be the same slice, in the same
+/// order, used to build `matcher` (see `build_fixed_word_matcher_for`).
+fn fixed_word_spans(matcher: &AhoCorasick, haystack: &str, tokens: &[AddedToken]) -> Vec<(usize, usize)> {
+    matcher
+        .find_iter(haystack)
+        .filter_map(|m| {
+            let token = &tokens[m.pattern().as_usize()];
+            let (mut start, mut end) = (m.start(), m.end());
+
+            if token.single_word && !is_fixed_word_boundary_match(haystack, start, end) {
+                return None;
+            }
+
+            if token.lstrip {
+                while start > 0 {
+                    let prev = haystack[..start].chars().next_back()?;
+                    if !prev.is_whitespace() {
+                        break;
+                    }
+                    start -= prev.len_utf8();
+                }
+            }
+            if token.rstrip {
+                while end < haystack.len() {
+                    let next = haystack[end..].chars().next()?;
+                    if !next.is_whitespace() {
+                        break;
+                    }
+                    end += next.len_utf8();
+                }
+            }
+
+            Some((start, end))
+        })
+        .collect()
+}
+
+/// Like `fixed_word_spans`, but when `fold_diacritics` is set, `matcher` was
+/// built over `fold`ed pattern content (see `build_fixed_word_matcher_for`)
+/// and so must run against `fold(haystack)` rather than `haystack` itself;
+/// the resulting spans are then translated back to `haystack`'s own byte
+/// offsets by char index, since `fold` maps exactly one input char to one
+/// output char in the same order — the same property
+/// `tone_normalize::canonicalize_for_matching` relies on.
+fn fixed_word_spans_for(
+    matcher: &AhoCorasick,
+    haystack: &str,
+    tokens: &[AddedToken],
+    fold_diacritics: bool,
+) -> Vec<(usize, usize)> {
+    if !fold_diacritics {
+        return fixed_word_spans(matcher, haystack, tokens);
+    }
+
+    let folded = fold(haystack);
+    let folded_char_index_of_byte = byte_to_char_index_table(&folded);
+    fixed_word_spans(matcher, &folded, tokens)
+        .into_iter()
+        .map(|(start, end)| {
+            (
+                char_index_to_byte_offset(haystack, folded_char_index_of_byte[start]),
+                char_index_to_byte_offset(haystack, folded_char_index_of_byte[end]),
+            )
+        })
+        .collect()
+}
+
+fn char_index_to_byte_offset(text: &str, char_index: usize) -> usize {
+    text.char_indices().nth(char_index).map(|(byte_offset, _)| byte_offset).unwrap_or(text.len())
+}
+
+fn is_fixed_word_boundary_match(haystack: &str, start: usize, end: usize) -> bool {
+    let before_ok = haystack[..start]
+        .chars()
+        .next_back()
+        .map_or(true, |c| !(c.is_alphanumeric() || c == '_'));
+    let after_ok = haystack[end..]
+        .chars()
+        .next()
+        .map_or(true, |c| !(c.is_alphanumeric() || c == '_'));
+    before_ok && after_ok
+}
+
+fn build_regex_patterns(options: &TokenizerOptions) -> Regex {
     let upper = upper_class();
-    let w = word_class();
+    let w = word_class(options.ignore_case);
 
     // Priority 1: Specials
     let specials = vec![
@@ -613,16 +1736,6 @@ fn build_regex_patterns(fixed_words: &[String]) -> Regex {
     let non_word_pattern = r"(?P<non_word>[^\w\s])";
 
     let mut all_patterns = Vec::new();
-    
-    // Add fixed words pattern if provided
-    if !fixed_words.is_empty() {
-        let escaped_words: Vec<String> = fixed_words
-            .iter()
-            .map(|word| word.replace(" ", r"\ "))
-            .collect();
-        let fixed_words_pattern = format!(r"(?P<fixed_words>\b{}\b)", escaped_words.join(r"\b|\b"));
-        all_patterns.push(fixed_words_pattern);
-    }
 
     all_patterns.extend(vec![
         specials_pattern,
@@ -641,81 +1754,140 @@ fn build_regex_patterns(fixed_words: &[String]) -> Regex {
         non_word_pattern.to_string(),
     ]);
 
-    let combined_pattern = format!("({})", all_patterns.join("|"));
+    let combined_pattern = if options.ignore_case {
+        format!("(?i)({})", all_patterns.join("|"))
+    } else {
+        format!("({})", all_patterns.join("|"))
+    };
     Regex::new(&combined_pattern).expect("Failed to compile regex")
 }
 
-fn extract_match(captures: &regex::Captures) -> Option<(String, TokenType)> {
+/// Build a lookup table mapping every char-boundary byte offset in `text` to
+/// the character index it falls at, so regex byte spans (always on char
+/// boundaries) can be reported as char spans. Indexable at `text.len()` (one
+/// past the last char) for end-of-match offsets.
+fn byte_to_char_index_table(text: &str) -> Vec<usize> {
+    let mut table = vec![0usize; text.len() + 1];
+    let mut char_index = 0;
+    for (byte_offset, _) in text.char_indices() {
+        table[byte_offset] = char_index;
+        char_index += 1;
+    }
+    table[text.len()] = char_index;
+    table
+}
+
+fn extract_match(captures: &regex::Captures) -> Option<(String, TokenType, usize, usize)> {
     if let Some(m) = captures.name("special") {
-        return Some((m.as_str().to_string(), TokenType::Special));
+        return Some((m.as_str().to_string(), TokenType::Special, m.start(), m.end()));
     }
     if let Some(m) = captures.name("abbr") {
-        return Some((m.as_str().to_string(), TokenType::Abbreviation));
+        return Some((m.as_str().to_string(), TokenType::Abbreviation, m.start(), m.end()));
     }
     if let Some(m) = captures.name("url") {
-        return Some((m.as_str().to_string(), TokenType::Url));
+        return Some((m.as_str().to_string(), TokenType::Url, m.start(), m.end()));
     }
     if let Some(m) = captures.name("email") {
-        return Some((m.as_str().to_string(), TokenType::Email));
+        return Some((m.as_str().to_string(), TokenType::Email, m.start(), m.end()));
     }
     if let Some(m) = captures.name("phone") {
-        return Some((m.as_str().to_string(), TokenType::Phone));
+        return Some((m.as_str().to_string(), TokenType::Phone, m.start(), m.end()));
     }
     if let Some(m) = captures.name("datetime") {
-        return Some((m.as_str().to_string(), TokenType::DateTime));
+        return Some((m.as_str().to_string(), TokenType::DateTime, m.start(), m.end()));
     }
     if let Some(m) = captures.name("name") {
-        return Some((m.as_str().to_string(), TokenType::Name));
+        return Some((m.as_str().to_string(), TokenType::Name, m.start(), m.end()));
     }
     if let Some(m) = captures.name("number") {
-        return Some((m.as_str().to_string(), TokenType::Number));
+        return Some((m.as_str().to_string(), TokenType::Number, m.start(), m.end()));
     }
     if let Some(m) = captures.name("emoji") {
-        return Some((m.as_str().to_string(), TokenType::Emoji));
+        return Some((m.as_str().to_string(), TokenType::Emoji, m.start(), m.end()));
     }
     if let Some(m) = captures.name("punct") {
-        return Some((m.as_str().to_string(), TokenType::Punct));
+        return Some((m.as_str().to_string(), TokenType::Punct, m.start(), m.end()));
     }
     if let Some(m) = captures.name("word_hyphen") {
-        return Some((m.as_str().to_string(), TokenType::WordHyphen));
+        return Some((m.as_str().to_string(), TokenType::WordHyphen, m.start(), m.end()));
     }
     if let Some(m) = captures.name("word") {
-        return Some((m.as_str().to_string(), TokenType::Word));
+        return Some((m.as_str().to_string(), TokenType::Word, m.start(), m.end()));
     }
     if let Some(m) = captures.name("sym") {
-        return Some((m.as_str().to_string(), TokenType::Symbol));
+        return Some((m.as_str().to_string(), TokenType::Symbol, m.start(), m.end()));
     }
     if let Some(m) = captures.name("non_word") {
-        return Some((m.as_str().to_string(), TokenType::NonWord));
-    }
-    if let Some(m) = captures.name("fixed_words") {
-        return Some((m.as_str().to_string(), TokenType::FixedWords));
+        return Some((m.as_str().to_string(), TokenType::NonWord, m.start(), m.end()));
     }
     None
 }
 
-// Placeholder implementations for normalize functions
-// These would need to be implemented based on the actual underthesea library
-fn normalize_characters_in_text(text: &str) -> String {
-    // This is a simplified implementation
-    // You would need to implement the actual Vietnamese character normalization
-    text.to_string()
+/// Apply `mode` to `text` (see `NormalizationMode`), using `old_style_tone`
+/// to pick new-style vs old-style tone-mark placement when `mode` is
+/// `NfcWithTonePlacement`.
+fn normalize_characters_in_text(text: &str, mode: NormalizationMode, old_style_tone: bool) -> String {
+    let style = if old_style_tone { ToneStyle::OldStyle } else { ToneStyle::NewStyle };
+    tone_normalize::normalize(text, mode, style)
 }
 
-fn token_normalize(token: &str, use_character_normalize: bool) -> String {
-    // This is a simplified implementation
-    // You would need to implement the actual token normalization
-    if use_character_normalize {
-        normalize_characters_in_text(token)
-    } else {
-        token.to_string()
-    }
+/// Fold visually-identical lookalike Latin/Cyrillic codepoints to their
+/// canonical Vietnamese form, then apply the same `NormalizationMode` the
+/// segment itself was normalized with.
+fn token_normalize(token: &str, mode: NormalizationMode, old_style_tone: bool) -> String {
+    let folded = tone_normalize::fold_lookalike_codepoints(token);
+    normalize_characters_in_text(&folded, mode, old_style_tone)
+}
+
+/// Strip Vietnamese diacritics down to their plain Latin base letter —
+/// "Tiếng Việt" -> "Tieng Viet" — by Unicode-decomposing each character and
+/// dropping the combining marks that fall out, with `đ`/`Đ` (not
+/// decomposable under NFD, unlike every toned/modified vowel) special-cased
+/// to `d`/`D`. Every input char maps to exactly one output char in the same
+/// order, so byte offsets found against the folded text can be translated
+/// back to the original by char index alone (see `fixed_word_spans_for`).
+pub fn fold(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            'đ' => 'd',
+            'Đ' => 'D',
+            _ => once(c).nfd().find(|d| !is_combining_mark(*d)).unwrap_or(c),
+        })
+        .collect()
+}
+
+fn is_combining_mark(c: char) -> bool {
+    matches!(c, '\u{0300}'..='\u{036F}')
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Write the CRF model dump format described on `CRFModel::load` to a
+    /// uniquely-named file under the OS temp dir, so tests can exercise
+    /// `load` against a real (if tiny) on-disk model.
+    fn write_test_model_file(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("parrotnlp_test_{}.crf", name));
+        let contents = "\
+# test-only model dump
+LABELS\tB-W\tI-W
+TEMPLATES\ttoken
+token=Bác#B-W\t2.0
+token=sĩ#I-W\t1.5
+token=bây#B-W\t1.8
+token=giờ#I-W\t1.3
+token=có#B-W\t1.9
+token=thể#I-W\t1.4
+B-W#I-W\t1.0
+I-W#B-W\t0.5
+B-W#B-W\t0.3
+I-W#I-W\t0.8
+";
+        fs::write(&path, contents).expect("write test model file");
+        path
+    }
+
     #[test]
     fn test_basic_tokenization() {
         let tokenizer = VietnameseTokenizer::new();
@@ -761,11 +1933,191 @@ mod tests {
         let tokenizer = VietnameseTokenizer::with_fixed_words(&fixed_words);
         let text = "Viện nghiên cứu khoa học";
         let tokens = tokenizer.tokenize_with_tags(text, true);
-        
+
         let has_fixed = tokens.iter().any(|t| t.token_type == TokenType::FixedWords);
         assert!(has_fixed, "Should detect fixed words token");
     }
-    
+
+    #[test]
+    fn test_fixed_words_aho_corasick_picks_longest_overlapping_match() {
+        // "Viện nghiên cứu" is a prefix of the longer "Viện nghiên cứu khoa học";
+        // leftmost-longest matching should prefer the longer one as a single span.
+        let fixed_words = vec![
+            "Viện nghiên cứu".to_string(),
+            "Viện nghiên cứu khoa học".to_string(),
+        ];
+        let tokenizer = VietnameseTokenizer::with_fixed_words(&fixed_words);
+        let tokens = tokenizer.tokenize_with_tags("Viện nghiên cứu khoa học Việt Nam", true);
+
+        let fixed: Vec<&Token> = tokens.iter().filter(|t| t.token_type == TokenType::FixedWords).collect();
+        assert_eq!(fixed.len(), 1, "Should merge the overlapping match into a single longest span");
+        assert_eq!(fixed[0].text, "Viện nghiên cứu khoa học");
+    }
+
+    #[test]
+    fn test_fixed_words_respects_word_boundaries() {
+        // "an" is a fixed entry, but it should not fire as a substring inside "quan".
+        let fixed_words = vec!["an".to_string()];
+        let tokenizer = VietnameseTokenizer::with_fixed_words(&fixed_words);
+        let tokens = tokenizer.tokenize_with_tags("quan trọng", true);
+
+        assert!(
+            !tokens.iter().any(|t| t.token_type == TokenType::FixedWords),
+            "Fixed word should not match inside a larger word"
+        );
+    }
+
+    #[test]
+    fn test_fixed_tokens_with_normalized_false_matches_raw_text_untouched() {
+        // Registered against old-style "hoà"; with `normalized: false` it
+        // must match only that exact raw spelling, never the new-style
+        // "hòa" normalization would otherwise fold it to.
+        let tokenizer = VietnameseTokenizer::with_fixed_tokens(&[AddedToken {
+            content: "hoà".to_string(),
+            single_word: true,
+            lstrip: false,
+            rstrip: false,
+            normalized: false,
+        }]);
+
+        let matched = tokenizer.tokenize_with_tags("hoà bình", true);
+        assert!(matched.iter().any(|t| t.token_type == TokenType::FixedWords && t.text == "hoà"));
+
+        let unmatched = tokenizer.tokenize_with_tags("hòa bình", true);
+        assert!(!unmatched.iter().any(|t| t.token_type == TokenType::FixedWords));
+    }
+
+    #[test]
+    fn test_fixed_tokens_rstrip_absorbs_trailing_whitespace() {
+        let tokenizer = VietnameseTokenizer::with_fixed_tokens(&[AddedToken {
+            content: "bác sĩ".to_string(),
+            single_word: true,
+            lstrip: false,
+            rstrip: true,
+            normalized: true,
+        }]);
+
+        let tokens = tokenizer.tokenize_with_tags("bác sĩ  giỏi", true);
+        let fixed = tokens.iter().find(|t| t.token_type == TokenType::FixedWords).unwrap();
+        assert_eq!(fixed.text, "bác sĩ  ");
+    }
+
+    #[test]
+    fn test_with_fixed_tokens_accepts_fixed_word_alias() {
+        // `FixedWord` is the name this request's proposed API used; it's an
+        // alias for `AddedToken`, so it must work everywhere the latter does.
+        use crate::added_vocabulary::FixedWord;
+
+        let tokenizer = VietnameseTokenizer::with_fixed_tokens(&[FixedWord {
+            content: "ACME Corp".to_string(),
+            single_word: true,
+            lstrip: false,
+            rstrip: false,
+            normalized: false,
+        }]);
+
+        let tokens = tokenizer.tokenize_with_tags("Làm việc tại ACME Corp hôm nay", true);
+        let fixed = tokens.iter().find(|t| t.token_type == TokenType::FixedWords).unwrap();
+        assert_eq!(fixed.text, "ACME Corp");
+    }
+
+    #[test]
+    fn test_fold_strips_tone_and_modified_vowel_marks() {
+        assert_eq!(fold("Tiếng Việt"), "Tieng Viet");
+    }
+
+    #[test]
+    fn test_fold_special_cases_d_with_stroke() {
+        assert_eq!(fold("đẹp trai"), "dep trai");
+    }
+
+    #[test]
+    fn test_with_options_ignore_case_matches_lowercase_abbreviation() {
+        let tokenizer = VietnameseTokenizer::with_options(TokenizerOptions { ignore_case: true, fold_diacritics: false });
+        let tokens = tokenizer.tokenize_with_tags("tp. hcm", true);
+        let has_abbr = tokens.iter().any(|t| t.token_type == TokenType::Abbreviation);
+        assert!(has_abbr, "ignore_case should match the lowercase form of an otherwise uppercase-only abbreviation pattern");
+    }
+
+    #[test]
+    fn test_with_options_fold_diacritics_matches_fixed_word_without_accents() {
+        let fixed_tokens = vec![AddedToken::new("Hà Nội").with_normalized(true)];
+        let mut tokenizer = VietnameseTokenizer::with_fixed_tokens(&fixed_tokens);
+        tokenizer.set_options(TokenizerOptions { ignore_case: false, fold_diacritics: true });
+
+        let tokens = tokenizer.tokenize_with_tags("Toi o Ha Noi", true);
+        let fixed = tokens.iter().find(|t| t.token_type == TokenType::FixedWords).unwrap();
+        assert_eq!(fixed.text, "Ha Noi");
+    }
+
+    #[test]
+    fn test_added_special_tokens_survive_as_atomic() {
+        let mut tokenizer = VietnameseTokenizer::new();
+        tokenizer.add_special_tokens(&["<mask>".to_string()]);
+
+        let text = "Xin chào <mask> bạn";
+        let tokens = tokenizer.tokenize_with_tags(text, true);
+
+        let mask_token = tokens
+            .iter()
+            .find(|t| t.token_type == TokenType::AddedToken)
+            .expect("Should detect the added token");
+        assert_eq!(mask_token.text, "<mask>");
+    }
+
+    #[test]
+    fn test_added_tokens_match_leftmost_not_longest_globally() {
+        // "</s>" is longer than "<s>", but "<s>" occurs earlier in the
+        // text; leftmost-longest must still surface
+        // both as atomic tokens rather than letting "</s>" preempt "<s>".
+        let mut tokenizer = VietnameseTokenizer::new();
+        tokenizer.add_special_tokens(&["<s>".to_string(), "</s>".to_string()]);
+
+        let text = "<s> xin chào </s>";
+        let tokens = tokenizer.tokenize_with_tags(text, true);
+
+        let added: Vec<&Token> = tokens.iter().filter(|t| t.token_type == TokenType::AddedToken).collect();
+        let added_texts: Vec<&str> = added.iter().map(|t| t.text.as_str()).collect();
+        assert_eq!(added_texts, vec!["<s>", "</s>"], "Both special tokens should survive as atomic spans");
+    }
+
+    #[test]
+    fn test_added_special_tokens_survive_serde_round_trip() {
+        let mut tokenizer = VietnameseTokenizer::new();
+        tokenizer.add_special_tokens(&["<s>".to_string(), "</s>".to_string()]);
+
+        let serialized = serde_json::to_string(&tokenizer).unwrap();
+        let restored: VietnameseTokenizer = serde_json::from_str(&serialized).unwrap();
+
+        let text = "<s> xin chào </s>";
+        let before = tokenizer.tokenize_with_tags(text, true);
+        let after = restored.tokenize_with_tags(text, true);
+        let summarize = |tokens: &[Token]| -> Vec<(String, TokenType, usize, usize)> {
+            tokens.iter().map(|t| (t.text.clone(), t.token_type.clone(), t.start, t.end)).collect()
+        };
+        assert_eq!(summarize(&before), summarize(&after), "Round-tripping a tokenizer must not change how it tokenizes");
+
+        let added: Vec<&str> = after
+            .iter()
+            .filter(|t| t.token_type == TokenType::AddedToken)
+            .map(|t| t.text.as_str())
+            .collect();
+        assert_eq!(added, vec!["<s>", "</s>"], "Special tokens must survive the round trip");
+    }
+
+    #[test]
+    fn test_assign_token_repoints_content() {
+        let mut tokenizer = VietnameseTokenizer::new();
+        tokenizer.add_special_tokens(&["[unused1]".to_string()]);
+        assert!(tokenizer.assign_token("[unused1]", "<entity>"));
+
+        let tokens = tokenizer.tokenize_with_tags("trước <entity> sau", true);
+        let has_reassigned = tokens
+            .iter()
+            .any(|t| t.token_type == TokenType::AddedToken && t.text == "<entity>");
+        assert!(has_reassigned, "Should match on the reassigned content");
+    }
+
     #[test]
     fn test_word_segmentation_basic() {
         let segmenter = VietnameseWordSegmenter::new();
@@ -804,16 +2156,68 @@ mod tests {
         println!("With fixed words: {:?}", words);
         // The "bác sĩ" should be treated as a single token in tokenization phase
     }
-    
+
+    #[test]
+    fn test_dictionary_segmentation_merges_known_compound() {
+        let mut dictionary = WordDictionary::new();
+        dictionary.insert("nghiên cứu", 1000);
+        dictionary.insert("khoa học", 500);
+
+        let syllables = vec![
+            "nghiên".to_string(),
+            "cứu".to_string(),
+            "khoa".to_string(),
+            "học".to_string(),
+        ];
+        let words = segment_with_dictionary(&dictionary, &syllables);
+
+        assert_eq!(words, vec!["nghiên cứu".to_string(), "khoa học".to_string()]);
+    }
+
+    #[test]
+    fn test_dictionary_segmentation_falls_back_to_single_syllables() {
+        let dictionary = WordDictionary::new();
+        let syllables = vec!["bây".to_string(), "giờ".to_string()];
+        let words = segment_with_dictionary(&dictionary, &syllables);
+
+        assert_eq!(words, vec!["bây".to_string(), "giờ".to_string()]);
+    }
+
+    #[test]
+    fn test_dictionary_segmentation_never_merges_across_non_word_token() {
+        // "cứu" and "100" would only ever be adjacent syllables in the
+        // dictionary's eyes; registering a (nonsensical) entry spanning them
+        // must not let the DP merge across the `Number` token between
+        // "nghiên cứu" and "100 khoa học".
+        let mut dictionary = WordDictionary::new();
+        dictionary.insert("nghiên cứu", 1000);
+        dictionary.insert("cứu 100", 1000);
+        dictionary.insert("khoa học", 500);
+
+        let tokens = vec![
+            Token { text: "nghiên".to_string(), token_type: TokenType::Word, start: 0, end: 6 },
+            Token { text: "cứu".to_string(), token_type: TokenType::Word, start: 7, end: 10 },
+            Token { text: "100".to_string(), token_type: TokenType::Number, start: 11, end: 14 },
+            Token { text: "khoa".to_string(), token_type: TokenType::Word, start: 15, end: 19 },
+            Token { text: "học".to_string(), token_type: TokenType::Word, start: 20, end: 23 },
+        ];
+        let words = segment_tokens_with_dictionary(&dictionary, &tokens);
+
+        assert_eq!(
+            words,
+            vec!["nghiên cứu".to_string(), "100".to_string(), "khoa học".to_string()]
+        );
+    }
+
     #[test]
     fn test_crf_model_basic() {
         let mut model = FastCRFSequenceTagger::new();
-        
-        // Test loading (will use dummy model)
-        let dummy_path = Path::new("nonexistent/model/path");
-        let result = model.load(dummy_path);
-        assert!(result.is_ok(), "Should handle missing model gracefully");
-        
+
+        let model_path = write_test_model_file("crf_model_basic");
+        let result = model.load(&model_path);
+        fs::remove_file(&model_path).ok();
+        assert!(result.is_ok(), "Should load a well-formed model file: {:?}", result.err());
+
         // Test prediction
         let features = vec![
             vec!["Bác".to_string()],
@@ -831,7 +2235,279 @@ mod tests {
             assert!(tag == "B-W" || tag == "I-W", "Invalid tag: {}", tag);
         }
     }
-    
+
+    #[test]
+    fn test_beam_decode_returns_n_best_with_probabilities() {
+        let mut model = CRFModel::new();
+        let model_path = write_test_model_file("beam_decode_nbest");
+        let result = model.load(&model_path);
+        fs::remove_file(&model_path).ok();
+        result.unwrap();
+
+        let tokens = vec![
+            "Bác".to_string(),
+            "sĩ".to_string(),
+            "bây".to_string(),
+            "giờ".to_string(),
+        ];
+
+        let results = model.beam_decode(&tokens, 4, 2);
+        assert_eq!(results.len(), 2);
+
+        let total_prob: f64 = results.iter().map(|(_, prob)| prob).sum();
+        assert!((total_prob - 1.0).abs() < 1e-6, "Probabilities should sum to ~1.0, got {}", total_prob);
+
+        for (tags, _) in &results {
+            assert_eq!(tags.len(), tokens.len());
+        }
+
+        // Best sequence should be at least as good as the second.
+        assert!(results[0].1 >= results[1].1);
+    }
+
+    #[test]
+    fn test_normalized_added_token_matches_alternate_tone_style() {
+        let mut tokenizer = VietnameseTokenizer::new();
+        tokenizer.add_added_tokens(vec![AddedToken::new("hòa").with_normalized(true)]);
+
+        // "hoà" is the old-style spelling of the same word registered as "hòa".
+        let tokens = tokenizer.tokenize_with_tags("xin hoà bình", true);
+        let matched = tokens
+            .iter()
+            .find(|t| t.token_type == TokenType::AddedToken)
+            .expect("Should match the added token in its alternate tone style");
+        assert_eq!(matched.text, "hoà");
+    }
+
+    #[test]
+    fn test_segmenter_exposes_added_token_registration() {
+        let mut segmenter = VietnameseWordSegmenter::new();
+        segmenter.add_special_tokens(&["<mask>".to_string()]);
+        assert!(segmenter.assign_token("<mask>", "<entity>"));
+    }
+
+    #[test]
+    fn test_character_normalize_repositions_tone_to_main_vowel() {
+        let tokenizer = VietnameseTokenizer::new();
+        let tokens = tokenizer.tokenize_with_tags("hoà", true);
+        assert_eq!(tokens[0].text, "hòa");
+    }
+
+    #[test]
+    fn test_character_normalize_old_style_moves_tone_to_last_vowel() {
+        let mut tokenizer = VietnameseTokenizer::new();
+        tokenizer.set_old_style_tone_placement(true);
+        let tokens = tokenizer.tokenize_with_tags("hòa", true);
+        assert_eq!(tokens[0].text, "hoà");
+    }
+
+    #[test]
+    fn test_token_normalize_folds_cyrillic_lookalikes() {
+        let tokenizer = VietnameseTokenizer::new();
+        // "раris" spelled with Cyrillic а/р that look identical to Latin a/p.
+        let tokens = tokenizer.tokenize_with_tags("раris", true);
+        assert_eq!(tokens[0].text, "paris");
+    }
+
+    #[test]
+    fn test_normalization_mode_none_skips_composition_and_repositioning() {
+        let mut tokenizer = VietnameseTokenizer::new();
+        tokenizer.set_normalization_mode(NormalizationMode::None);
+
+        // Combining grave accent (not precomposed) on the syllable's final vowel.
+        let text = "hoa\u{0300}";
+        let tokens = tokenizer.tokenize_with_tags(text, true);
+        assert_eq!(tokens[0].text, text, "NormalizationMode::None should leave text untouched");
+    }
+
+    #[test]
+    fn test_normalization_mode_nfc_composes_without_repositioning_tone() {
+        let mut tokenizer = VietnameseTokenizer::new();
+        tokenizer.set_normalization_mode(NormalizationMode::Nfc);
+
+        // Combining grave accent placed on the final vowel: NFC composes it
+        // to the precomposed "hoà", but new-style orthography would move the
+        // tone onto the first vowel ("hòa") — that repositioning step should
+        // not run under plain `Nfc`.
+        let tokens = tokenizer.tokenize_with_tags("hoa\u{0300}", true);
+        assert_eq!(tokens[0].text, "hoà");
+    }
+
+    #[test]
+    fn test_normalization_mode_nfc_with_tone_placement_is_the_default() {
+        let tokenizer = VietnameseTokenizer::new();
+        // Same decomposed input as the `Nfc`-only test above, but under the
+        // default mode the tone mark is also repositioned to "hòa".
+        let tokens = tokenizer.tokenize_with_tags("hoa\u{0300}", true);
+        assert_eq!(tokens[0].text, "hòa");
+    }
+
+    #[test]
+    fn test_tokenize_with_offsets_stays_in_sync_when_normalization_changes_char_count() {
+        // "hoa" + combining grave (4 chars) composes down to "hòa" (3 chars)
+        // under NFC; `Token::start`/`end` must index into `normalized_source`
+        // (3 chars), not the original 4-char input, or a downstream consumer
+        // indexing by char offset would run past the end of the real text.
+        let tokenizer = VietnameseTokenizer::new();
+        let (tokens, normalized_source) = tokenizer.tokenize_with_offsets("hoa\u{0300} binh", true);
+
+        assert_eq!(normalized_source.chars().count(), "hòa binh".chars().count());
+        let last = tokens.last().unwrap();
+        assert!(last.end <= normalized_source.chars().count());
+        assert_eq!(&normalized_source.chars().collect::<Vec<_>>()[tokens[0].start..tokens[0].end].iter().collect::<String>(), "hòa");
+    }
+
+    #[test]
+    fn test_beam_decode_empty_input() {
+        let model = CRFModel::new();
+        assert!(model.beam_decode(&[], 4, 3).is_empty());
+    }
+
+    #[test]
+    fn test_load_missing_model_file_is_an_error() {
+        let mut model = CRFModel::new();
+        let result = model.load(Path::new("nonexistent/model/path"));
+        assert!(result.is_err(), "Missing model file should be a real error, not a silent dummy model");
+    }
+
+    #[test]
+    fn test_load_malformed_model_file_is_an_error() {
+        let path = std::env::temp_dir().join("parrotnlp_test_malformed.crf");
+        fs::write(&path, "LABELS\tB-W\tI-W\ntoken=x#UNKNOWN-LABEL\t1.0\n").unwrap();
+
+        let mut model = CRFModel::new();
+        let result = model.load(&path);
+        fs::remove_file(&path).ok();
+
+        assert!(result.is_err(), "A weight key referencing an unknown label should be rejected");
+    }
+
+
+    #[test]
+    fn test_viterbi_decode_single_token_uses_start_and_end_transitions_only() {
+        let path = std::env::temp_dir().join("parrotnlp_test_viterbi_single_token.crf");
+        fs::write(&path, "\
+LABELS\tB-W\tI-W
+TEMPLATES\ttoken
+token=Bác#B-W\t1.0
+token=Bác#I-W\t0.5
+B-W#<END>\t-10.0
+I-W#<END>\t0.0
+").unwrap();
+
+        let mut model = CRFModel::new();
+        model.load(&path).unwrap();
+        fs::remove_file(&path).ok();
+
+        // Emission alone favors B-W (1.0 > 0.5), but the end transition
+        // penalizes ending the sequence on B-W heavily enough to flip the
+        // single-token decode to I-W.
+        let tags = model.viterbi_decode(&vec!["Bác".to_string()]);
+        assert_eq!(tags, vec!["I-W".to_string()]);
+    }
+
+    #[test]
+    fn test_viterbi_decode_start_transition_influences_first_label() {
+        let path = std::env::temp_dir().join("parrotnlp_test_viterbi_start_trans.crf");
+        fs::write(&path, "\
+LABELS\tB-W\tI-W
+TEMPLATES\ttoken
+token=Bác#B-W\t1.0
+token=Bác#I-W\t0.9
+token=sĩ#B-W\t0.1
+token=sĩ#I-W\t1.0
+<START>#I-W\t5.0
+B-W#B-W\t0.0
+B-W#I-W\t0.0
+I-W#B-W\t0.0
+I-W#I-W\t0.0
+").unwrap();
+
+        let mut model = CRFModel::new();
+        model.load(&path).unwrap();
+        fs::remove_file(&path).ok();
+
+        // Emission alone favors B-W for the first token, but the learned
+        // start transition strongly favors opening the sequence in I-W.
+        let tags = model.viterbi_decode(&vec!["Bác".to_string(), "sĩ".to_string()]);
+        assert_eq!(tags[0], "I-W");
+    }
+
+    #[test]
+    fn test_load_malformed_end_transition_key_is_an_error() {
+        let path = std::env::temp_dir().join("parrotnlp_test_malformed_end_trans.crf");
+        fs::write(&path, "LABELS\tB-W\tI-W\nUNKNOWN-LABEL#<END>\t1.0\n").unwrap();
+
+        let mut model = CRFModel::new();
+        let result = model.load(&path);
+        fs::remove_file(&path).ok();
+
+        assert!(result.is_err(), "An end-transition key referencing an unknown label should be rejected");
+    }
+
+    #[test]
+    fn test_predict_with_scores_confidence_is_high_when_emission_dominates() {
+        let path = std::env::temp_dir().join("parrotnlp_test_predict_with_scores.crf");
+        fs::write(&path, "\
+LABELS\tB-W\tI-W
+TEMPLATES\ttoken
+token=Bác#B-W\t10.0
+token=Bác#I-W\t-10.0
+").unwrap();
+
+        let mut model = CRFModel::new();
+        model.load(&path).unwrap();
+        fs::remove_file(&path).ok();
+
+        let scored = model.predict_with_scores(&vec!["Bác".to_string()], 2);
+        assert_eq!(scored.len(), 1);
+        assert_eq!(scored[0].0, "B-W");
+        assert!(scored[0].1 > 0.9, "confidence should be near 1.0 when one label dominates: {:?}", scored[0]);
+    }
+
+    #[test]
+    fn test_predict_with_scores_credits_viterbi_path_the_beam_pruned_away() {
+        // The first token's emission slightly favors B-W, so a width-1 beam
+        // commits to B-W greedily and never explores I-W. But I-W#I-W is
+        // weighted so heavily that the globally optimal (Viterbi) path is
+        // actually I-W,I-W — a path the width-1 beam discarded after the
+        // first token and so never carries as a survivor.
+        let path = std::env::temp_dir().join("parrotnlp_test_predict_with_scores_divergence.crf");
+        fs::write(&path, "\
+LABELS\tB-W\tI-W
+TEMPLATES\ttoken
+token=a#B-W\t1.0
+token=a#I-W\t0.9
+B-W#B-W\t-10.0
+B-W#I-W\t-10.0
+I-W#B-W\t-10.0
+I-W#I-W\t10.0
+").unwrap();
+
+        let mut model = CRFModel::new();
+        model.load(&path).unwrap();
+        fs::remove_file(&path).ok();
+
+        let tokens = vec!["a".to_string(), "b".to_string()];
+        let viterbi = model.viterbi_decode(&tokens);
+        assert_eq!(viterbi, vec!["I-W", "I-W"], "I-W,I-W should be the globally optimal path");
+
+        let narrow_beam = model.beam_decode(&tokens, 1, 1);
+        assert_ne!(
+            narrow_beam[0].0[0], "I-W",
+            "a width-1 beam is expected to prune I-W at the first token, confirming the divergence this test exercises"
+        );
+
+        let scored = model.predict_with_scores(&tokens, 1);
+        assert_eq!(scored[0].0, "I-W");
+        assert_eq!(scored[1].0, "I-W");
+        assert!(
+            scored[0].1 > 0.9 && scored[1].1 > 0.9,
+            "viterbi_decode's own top choice must carry positive confidence even when the beam pruned it away: {:?}",
+            scored
+        );
+    }
+
     #[test]
     fn test_feature_extraction() {
         let model = CRFModel::new();
@@ -851,4 +2527,26 @@ mod tests {
         assert!(features_0.iter().any(|f| f.contains("prev_token=<BOS>")));
         assert!(features_1.iter().any(|f| f.contains("prev_token=Bác")));
     }
+
+    #[test]
+    fn test_pos_feature_templates_cover_shape_and_affixes() {
+        let model = CrfTagger::<DynamicLabels>::with_labels(
+            vec!["N".to_string(), "V".to_string()],
+            vec![
+                "shape".to_string(),
+                "prefix2".to_string(),
+                "suffix2".to_string(),
+                "contains_digit".to_string(),
+                "contains_hyphen".to_string(),
+            ],
+        );
+        let tokens = vec!["Covid-19".to_string()];
+        let features = model.extract_features(&tokens, 0);
+
+        assert!(features.iter().any(|f| f.starts_with("shape=")));
+        assert!(features.contains(&"prefix2=Co".to_string()));
+        assert!(features.contains(&"suffix2=19".to_string()));
+        assert!(features.contains(&"contains_digit=true".to_string()));
+        assert!(features.contains(&"contains_hyphen=true".to_string()));
+    }
 }
\ No newline at end of file